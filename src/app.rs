@@ -1,27 +1,31 @@
-use std::borrow::BorrowMut;
 use std::error;
 use std::f64::consts::PI;
-use std::time::{SystemTime, UNIX_EPOCH};
-use futures::future::join_all;
-use threadpool::ThreadPool;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
 use tui::backend::Backend;
-use tui::layout::{Alignment, Constraint};
+use tui::layout::Constraint;
 use tui::style::{Color, Style};
-use tui::symbols;
 use tui::terminal::Frame;
-use tui::text::Span;
-use tui::widgets::{Axis, Block, Borders, BorderType, Chart, Dataset, GraphType, Paragraph};
-use crate::quaternions::Quaternion;
+use tui::text::{Span, Spans};
+use tui::widgets::{Axis, Block, BorderType, Chart, Dataset, GraphType};
+use crate::clock::{Clock, Playback, SystemClock};
+use crate::keymap::Keymap;
+use crate::level::Level;
+use crate::raster;
+use crate::render::{Ascii, HalfBlocks, Renderer};
+use crate::svg::{self, ImageOptions};
+use crate::scene::{add, camera_depth, length, scale, sub, Point3d, Points2d, Polygon, Polyhedron, Transform, LINE_POINTS, NEAR_PLANE};
 
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
-type Point3d = (f64,f64,f64);
-type Point2d = (f64,f64);
+/// Height of a terminal cell divided by its width, unless configured otherwise.
+pub const CHARACTER_RATIO: f64 = 1.8;
 
-type Points3d = Vec<(f64,f64,f64)>;
-type Points2d = Vec<(f64,f64)>;
+/// Where a level is saved when it wasn't loaded from a file.
+pub const DEFAULT_LEVEL_PATH: &str = "level.json";
 
 
 /// Application.
@@ -32,603 +36,404 @@ pub struct App {
     
     pub world: WorldMetrics,
 
-    polygons: Vec<Polygon>,
-}
+    /// Root node of the scene graph.
+    pub scene: Polyhedron,
 
-impl Default for App {
-    fn default() -> Self {
-        Self { 
-            running: true , 
-            world: WorldMetrics::default(), 
-            polygons: Vec::new()
-        }
-    }
-}
+    /// Description of the scene, as loaded and saved.
+    pub level: Level,
 
-impl App {
-    /// Constructs a new instance of [`App`].
-    pub fn new() -> Self {
-        Self::default()
-    }
+    /// File the level was loaded from, and is saved to.
+    pub level_path: Option<PathBuf>,
 
-    /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    /// Files the scene was built from, with their modification times when it was built.
+    pub watched: Vec<(PathBuf, Option<SystemTime>)>,
 
-    /// Renders the user interface widgets. 
-    pub fn render<B: Backend>(&mut self, frame: &mut Frame<'_, B>) {
+    /// Why the last reload failed; the previous scene stays until the files are fixed.
+    pub reload_error: Option<String>,
 
-        // This is where you add new widgets.
-        // See the following resources:
-        // - https://docs.rs/tui/0.16.0/tui/widgets/index.html
-        // - https://github.com/fdehau/tui-rs/tree/v0.16.0/examples
-        self.world.update_timestamp();
-        self.polygons.clear();
+    /// What came of the last save or export, shown in the title: what was written, or why it failed;
+    /// or the last [`Event::Custom`](crate::event::Event::Custom) message.
+    pub message: Option<Result<String, String>>,
 
-        frame.size().area();
+    /// How points are drawn into terminal cells.
+    pub renderer: Renderer,
 
-        let res_x = frame.size().width as f64;
-        let res_y = frame.size().height as f64;
+    /// Whether polygons are drawn as outlines only, rather than filled.
+    pub wireframe: bool,
 
-        // println!("ratio {}", self.n);
-        let character_ratio = 1.8;
-        let aspect_ratio = res_x / res_y / character_ratio;
-        let aspect_padding = (aspect_ratio-1.0)/2.0; 
+    pub camera_mode: CameraMode,
 
-        let x_left = -1.0-aspect_padding;
-        let x_right = 1.0+aspect_padding;
+    /// Point the camera turns around in [`CameraMode::Orbit`].
+    pub orbit_target: Point3d,
 
-        let x_left_label = format!("{x_left:0.2}");
-        let x_right_label = format!("{x_right:0.2}");
-        let x_middle_label = format!("{:0.2}", (x_left + x_right)/2.0);
+    /// Height of a terminal cell divided by its width.
+    pub character_ratio: f64,
 
-        let q0 = Quaternion::new(0.0,0.0,0.0,0.0);
-        let q = Quaternion::new(0.0,1.0,1.0,1.0);
+    /// Size of the terminal drawn on, set by [`App::resize`].
+    pub viewport: Viewport,
 
-        let pentagram_vert: Points3d = (0..5).into_iter().map(|n| {
-            let step = 2.0*PI/5.0;
-            let offset = -PI/10.0;
-            let angle = n as f64 *2.0* step + offset;
-            let scale = 1.0f64;
-            (angle.cos() * scale, angle.sin() * scale , 0.0)
-        }).collect();
+    /// What the keys do.
+    pub keymap: Keymap,
 
-        let mut pentagram = Polygon::new(
-            pentagram_vert.clone(),
-            Color::Red,
-            (0.0,0.0,7.0),
-            q.clone()
-        );
+    /// Time of each frame drawn, for animations.
+    pub clock: Box<dyn Clock>,
 
-        let mut pentagram2 = Polygon::new(
-            pentagram_vert,
-            Color::Red,
-            (0.0,0.0,7.0),
-            q.clone()
-        );
-        let ftr = ( 1.0, 1.0, 1.0);  // naming : (front || back) && (top || bottom) && (left || right)
-        let ftl = (-1.0, 1.0, 1.0);
-        let fbr = ( 1.0,-1.0, 1.0);
-        let fbl = (-1.0,-1.0, 1.0);
-        let btr = ( 1.0, 1.0,-1.0);
-        let btl = (-1.0, 1.0,-1.0);
-        let bbr = ( 1.0,-1.0,-1.0);
-        let bbl = (-1.0,-1.0,-1.0);
-
-        
-        let mut unit_cube = Polyhedron::new(
-            vec![
-                Polygon::new(
-                    vec![
-                        ftr, fbr, fbl, ftl
-                    ],
-                    Color::Blue,
-                    (0.0,0.0,7.0), q.clone(),
-                ),
-                Polygon::new(
-                    vec![
-                        ftr, fbr, bbr, btr
-                    ],
-                    Color::Green,
-                    (0.0,0.0,7.0),q.clone()
-                ),
-                Polygon::new(
-                    vec![                        
-                        btr, bbr, bbl, btl
-                    ],
-                    Color::LightYellow,
-                    (0.0,0.0,7.0),q.clone()
-                ),
-                Polygon::new(
-                    vec![
-                        ftl, fbl, bbl, btl
-                    ], 
-                    Color::Magenta,
-                    (0.0,0.0,7.0),q.clone()
-                ),
-
-            ]
-        );
+    /// Speed of animations, which can be paused and stepped.
+    pub playback: Playback,
 
-        let pentaface: Points3d = (0..5).into_iter().map(|n| {
-            let step = PI/5.0;
-            let offset = -PI/10.0 * 0.0;
-            let angle = n as f64 *2.0* step + offset;
-            let scale = 0.5f64;
-            (angle.cos() * scale, 0.0, angle.sin() * scale )
-        }).collect();
+    /// Size and look of frames exported with [`App::export_svg`] and [`App::export_png`].
+    pub image: ImageOptions,
+}
 
+/// Something drawn in a frame.
+#[derive(Clone, Copy, Debug)]
+pub enum Shape<'a> {
+    /// The outline of a polygon, filled unless drawing wireframes.
+    Polygon(&'a Polygon),
+    /// Projected points of one colour.
+    Points(Color, &'a Points2d),
+}
 
-        let phi = (1.0 + 5.0f64.sqrt())/2.0;
-        let phi_i = 1.0 / phi;
-        let dodecavertexes = vec![
-            (1.0   , 1.0   , 1.0  ),
-            (1.0   , 1.0   , -1.0 ),
-            (1.0   , -1.0  , 1.0  ),
-            (1.0   , -1.0  , -1.0 ),
-
-            (0.0   , phi_i, phi   ),
-            (0.0   , phi_i, -phi  ),
-            (0.0   , -phi_i, phi  ),
-            (0.0   , -phi_i, -phi ),
-            
-            (-1.0   , 1.0   , 1.0  ),
-            (-1.0   , 1.0   , -1.0 ),
-            (-1.0   , -1.0  , 1.0  ),
-            (-1.0   , -1.0  , -1.0 ),
-
-            (phi_i , phi  , 0.0   ),
-            (phi_i , -phi  , 0.0  ),
-            (-phi_i , phi  , 0.0  ),
-            (-phi_i , -phi  , 0.0 ),
-            
-            (phi   , 0.0  , phi_i ),
-            (phi   , 0.0  , -phi_i),
-            (-phi  , 0.0  , phi_i ),
-            (-phi  , 0.0  , -phi_i),
-        ];
-
-        let len = |(a0,a1,a2) : Point3d|   -> f64 {
-            ((a0*a0) + (a1*a1) + (a2*a2)).sqrt()
-        };
-        let sub = |(a0,a1,a2): Point3d, (b0,b1,b2) : Point3d|   -> Point3d {
-            (b0-a0 , b1-a1 , b2-a2)
-        };
+/// How the movement keys move the camera.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Turns in place and moves along the world axes.
+    #[default]
+    Fly,
+    /// Turns around [`App::orbit_target`], moving closer or farther away.
+    Orbit,
+}
 
-        let mut dodecaskeleton : Points3d = Vec::new();
+impl FromStr for CameraMode {
+    type Err = String;
 
-        for v0 in dodecavertexes.clone() {
-            let mut shortest_distance = 10.0;
-            let mut closest_vertex : Point3d = v0;
-            for v1 in dodecavertexes.clone() {
-                let distance = len(sub(v0,v1));
-                if distance < shortest_distance { 
-                    shortest_distance = distance;
-                    closest_vertex = v1;
-                }
-            } 
-            dodecaskeleton.push(closest_vertex);
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "fly" => Ok(CameraMode::Fly),
+            "orbit" => Ok(CameraMode::Orbit),
+            _ => Err(format!("unknown camera '{name}', expected orbit or fly")),
         }
-
-        let z = 0.0;
-        let a = 1.0;
-        let b = 0.5;
-        let c = 0.8;
-        let eye = Polygon::new(
-            vec![
-                (-a, z, z),
-                (-c, b, z),
-                (c, b, z),
-                (a, z, z),
-                (c, -b, z),
-                (-c, -b, z),
-            ],
-            Color::Red,
-            (-1.0, -1.0, 8.0),
-            q0
-        );
-
-        self.polygons.push(eye); 
-
-
-
-        // self.polygons.push(pentagram2);
-        self.polygons.push(pentagram);
-        // self.polygons.push(onebyone);
-
-        // // dodecahedron.render(&self);
-        // for p in unit_cube.polygons {
-        //     self.polygons.push(p)
-        // }
-
-
-        //remove things that are too close or behind the camera
-        self.polygons.retain(|p| {
-            p.center.2 + p.translation.2 + self.world.world_translation_z > 1.0
-        });
-
-        self.render_polygons();
-
-        self.polygons.sort_by(|a,b| {   // to render in the correct order
-            b.center.2.partial_cmp(&a.center.2.to_owned()).unwrap()
-        });
-
-        let datasets = self.polygons.iter().rev().map(|p|{
-            p.as_dataset()
-        }).collect();
-        
-        frame.render_widget(
-            Chart::new(datasets)
-                .block(Block::default().title(" 3T ").border_type(BorderType::Plain))
-                .style(Style::default().fg(Color::White))
-                .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)))
-
-                .x_axis(Axis::default()
-                    //.title(Span::styled("X Axis", Style::default().fg(Color::Red)))
-                    .style(Style::default().fg(Color::White))
-                    .bounds([x_left, x_right])
-                    .labels([x_left_label, x_middle_label, x_right_label].iter().cloned().map(Span::from).collect()))
-                .y_axis(Axis::default()
-                    //.title(Span::styled("Y Axis", Style::default().fg(Color::Red)))
-                    .style(Style::default().fg(Color::White))
-                    .bounds([-1.0, 1.0])
-                    .labels(["-1.0", "0", "1.0"].iter().cloned().map(Span::from).collect())),
-            frame.size(),
-        )
-    }
-
-    fn render_polygons(&mut self) {
-        (0..self.polygons.len()).for_each( |i | {
-            self.polygons.get_mut(i).unwrap().render(self.world.clone());
-        })
     }
 }
 
-// fn projected_to_char_coord(x: f64, y:f64, aspect_ratio: f64, char_ratio: f64) -> (u16,u16) {
-    
-// }
-
-
-
-const LINE_POINTS: i32 = 200;
-const LINE_RESOLUTION: f64 = 1.0 / (LINE_POINTS as f64);
-struct Line{
-    points: Points3d,
-    start: Point3d,
-    end: Point3d,
-    center: Point3d,
-    color: Color,
-}
-
-impl Line{
-    fn new(start: Point3d, end: Point3d, color: Color) -> Line{
-        Line{
-            points: Line::interpolate(&start, &end),
-            start,
-            end,
-            center: Line::center_point_of(&start, &end),
-            color,
+impl Default for App {
+    fn default() -> Self {
+        let level = Level::demo();
+        Self { 
+            running: true , 
+            world: WorldMetrics::default(), 
+            scene: level.build(Path::new("")).expect("the demo level only uses built in meshes"),
+            level,
+            level_path: None,
+            watched: Vec::new(),
+            reload_error: None,
+            message: None,
+            renderer: Renderer::default(),
+            wireframe: true,
+            camera_mode: CameraMode::default(),
+            orbit_target: (0.0, 0.0, 0.0),
+            character_ratio: CHARACTER_RATIO,
+            viewport: Viewport::default(),
+            keymap: Keymap::default(),
+            clock: Box::new(SystemClock),
+            playback: Playback::default(),
+            image: ImageOptions::default(),
         }
     }
+}
 
-
-
-    fn len(&self) -> f64 {
-        let s = Quaternion::from(self.start);
-        let e = Quaternion::from(self.end);
-        let d = e - s;  
-        d.len()
+impl App {
+    /// Constructs a new instance of [`App`].
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn approximate_projected_size(start: Point3d, end: Point3d, e: Point3d, t: Point3d, character_ratio: f64) -> f64 {
-        let (dx, dy) =  Line::projected_extremeties(start, end, e, t);
-        let (cx, cy) = (dx*character_ratio, dy);
-        
-        ((cx*cx) + (cy*cy)).sqrt()
+    /// Handles the tick event of the terminal.
+    pub fn tick(&mut self) {
+        if self.watched.iter().any(|(path, time)| modified(path) != *time) {
+            self.reload_error = self.reload_level().err().map(|e| e.to_string());
+        }
     }
 
-    fn projected_extremeties(start: Point3d, end: Point3d, e: Point3d, t: Point3d) -> Point2d {
-        let (sx,sy) = project_point(start, e, t);
-        let (ex, ey) = project_point(end, e, t);
-        (ex-sx, ey-sy)
+    /// Shows a level (`.json`), or a model or point cloud file in front of the camera.
+    pub fn open(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|e| e == "json") {
+            return self.load_level(path);
+        }
+        self.level = Level::model(path)?;
+        self.level_path = None;
+        self.reload_level()?;
+        self.frame_scene();
+        Ok(())
     }
 
-    fn center_point_of(a: &Point3d, b: &Point3d) -> Point3d {
-        let (a0, a1, a2) = a;
-        let (b0, b1, b2) = b;
-        let c = |a,b| -> f64 {(a+b)/2.0};
-        (c(a0, b0),c(a1, b1),c(a2, b2))
+    /// Replaces the scene with the level in the file at `path` and moves the camera to its start.
+    pub fn load_level(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        self.level_path = Some(path.as_ref().to_path_buf());
+        self.reload_level()?;
+        self.level.apply_camera(&mut self.world);
+        Ok(())
     }
 
-    pub fn interpolate(start: &Point3d, end: &Point3d) -> Points3d {
-        let (endx, endy, endz) = end;
-        let (startx, starty, startz) = start;
-        let (deltax, deltay, deltaz): Point3d = ((endx-startx)*LINE_RESOLUTION,(endy-starty)*LINE_RESOLUTION,(endz-startz)*LINE_RESOLUTION);  //end.subtract(&start).scale(LINE_RESOLUTION);
-        
-        let data : Points3d = (0..LINE_POINTS).map(|i|{
-            (
-                (startx + (deltax*(i as f64))), 
-                (starty + (deltay*(i as f64))), 
-                (startz + (deltaz*(i as f64))),
-            )
-            //start.clone().add(&deltas.scale(i as f64)).into()
-        }).collect();
-        data
+    /// Rebuilds the scene from the level file and the mesh files it reads, leaving the camera where it is.
+    pub fn reload_level(&mut self) -> AppResult<()> {
+        // remember the times first, so that a file changing while it's read is read again
+        self.watched.clear();
+        let (level, directory) = match &self.level_path {
+            Some(path) => {
+                self.watched.push((path.clone(), modified(path)));
+                (Level::load(path)?, path.parent().unwrap_or(Path::new("")).to_path_buf())
+            }
+            None => (self.level.clone(), PathBuf::new()),
+        };
+        self.watched.extend(level.files(&directory).into_iter().map(|file| {
+            let time = modified(&file);
+            (file, time)
+        }));
+        self.scene = level.build(&directory)?;
+        self.level = level;
+        Ok(())
     }
-}
-
-#[derive(Debug)]
-struct Polygon{
-    projection: Points2d,
-    vertices: Points3d,
-    points: Points3d,
-    center /*of gravity*/: Point3d,
-    color: Color,
-    translation: Point3d,
-    rotation : Quaternion
-}
-
 
-impl Polygon {
-    fn new(vertices: Points3d, color : Color, offset: Point3d, q: Quaternion) -> Self{
-        Self{
-            vertices: vertices.clone(),
-            center: (0.0f64,0.0f64,0.0f64),
-            points: Vec::new(),
-            color,
-            translation: offset,
-            rotation: q,
-            projection: Vec::new(),
-        }
-    }
-    fn generate_sides_and_center(&mut self) -> Vec<Line> {
-        let (c0, c1, c2) = self.center.borrow_mut();
-        let mut sides : Vec<Line> = Vec::new();
-        for i in 1..self.vertices.len() { //  
-            let l = Line::new(
-                self.vertices[i-1].clone(),
-                self.vertices[i].clone(),
-                self.color
-            );
-            let (l0, l1, l2) = l.center;   // centering
-            *c0 += l0; *c1 += l1; *c2 += l2; // centering
-            sides.push(l); 
-        }
-        let l = Line::new(
-            self.vertices[self.vertices.len()-1].clone(),
-            self.vertices[0].clone(),
-            self.color
-        );
-        let (l0, l1, l2) = l.center; // centering
-        *c0 += l0; *c1 += l1; *c2 += l2; // centering
-        
-        sides.push(l);
-        
-        let n = sides.len() as f64; // centering
-        *c0 /= n; *c1 /= n; *c2 /= n; // centering
-        
-        sides
+    /// Moves the camera back from the middle of the scene until all of it is in view.
+    pub fn frame_scene(&mut self) {
+        let Some((low, high)) = self.scene.bounds(&Transform::identity()) else {
+            return;
+        };
+        let center = scale(add(low, high), 0.5);
+        let radius = length(sub(high, low)) / 2.0;
+        let distance = (radius / (self.world.fov / 2.0).sin()).max(radius + 2.0 * self.world.near_plane);
+        self.world.camera_pitch = 0.0;
+        self.world.camera_yaw = 0.0;
+        self.world.set_camera_position(sub(center, (0.0, 0.0, distance)));
+        self.orbit_target = center;
     }
 
-
-    fn generate_points(&mut self) {
-        let mut sides = self.generate_sides_and_center();
-        let mut points : Points3d = Vec::new();
-        for i in 0..sides.len() {
-            points.append(&mut sides[i].points);
+    /// Switches the camera mode; an orbiting camera turns around the middle of the scene.
+    pub fn set_camera_mode(&mut self, mode: CameraMode) {
+        self.camera_mode = mode;
+        if mode == CameraMode::Orbit {
+            self.orbit_target = match self.scene.bounds(&Transform::identity()) {
+                Some((low, high)) => scale(add(low, high), 0.5),
+                None => add(self.world.camera_position(), self.world.camera_direction()),
+            };
+            self.orbit(0.0, 0.0, 1.0);
         }
-        self.points = points;
     }
 
-    fn as_dataset(&self) -> Dataset {
-        Dataset::default()
-            .marker(symbols::Marker::Braille)
-            .graph_type(GraphType::Line)
-            .style(Style::default().fg(self.color))
-            .data(&self.projection)
+    /// Turns the camera around [`App::orbit_target`] and scales its distance to it by `zoom`.
+    pub fn orbit(&mut self, pitch: f64, yaw: f64, zoom: f64) {
+        let distance = (length(sub(self.world.camera_position(), self.orbit_target)) * zoom).max(self.world.near_plane / 2.0);
+        self.world.camera_pitch = (self.world.camera_pitch + pitch).clamp(-PI / 2.0 + 0.01, PI / 2.0 - 0.01);
+        self.world.camera_yaw += yaw;
+        self.world.set_camera_position(sub(self.orbit_target, scale(self.world.camera_direction(), distance)));
     }
 
-    fn render(&mut self, world: WorldMetrics){
-        self.generate_points();
-        self.transform(&world);
-        self.project(&world);
+    /// Writes the level, with the camera's current position as its start, back to the file it came from, returning the file's path.
+    pub fn save_level(&mut self) -> AppResult<PathBuf> {
+        self.level.store_camera(&self.world);
+        let path = self.level_path.get_or_insert_with(|| PathBuf::from(DEFAULT_LEVEL_PATH)).clone();
+        self.level.save(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        // our own write is not a change to reload
+        self.watched.retain(|(file, _)| *file != path);
+        self.watched.push((path.clone(), modified(&path)));
+        self.reload_error = None;
+        Ok(path)
     }
-    
-    fn project(&mut self, world: &WorldMetrics){
-        let fov = PI/2.0; // 90deg
-        let ez = 1.0/((fov/2.0).tan());
-        self.projection = self.points.iter().map(|a| {
-            project_point(a.clone(), (0.0, 0.0, ez), (world.camera_pitch,world.camera_yaw,0.0)) 
-        }).collect()
-    } 
-
-    pub fn transform(&mut self, world: &WorldMetrics) {
-        let q = &self.rotation;
-        for i in 0..self.points.len(){
-            let a = self.points.get(i).unwrap();
-
-            let (offx, offy, offz) = &self.translation;
-
-            // let t = world.frame_timestamp; 
-            // let w = t*PI/5000.0;
-            let w = 0.0;
-
-            let (xx,yy,zz) = q.rotate_point(a.clone(), w);
-            
-            let (x,y, z) = self.points.get_mut(i).unwrap();
-
-            *x = xx + offx + world.world_translation_x;
-            *y = yy + offy + world.world_translation_y; 
-            *z = zz + offz + world.world_translation_z;
-        }
+
+    /// Writes the current frame to a new SVG file named after its time, returning the file's path.
+    pub fn export_svg(&mut self) -> AppResult<PathBuf> {
+        let path = unused_path(&format!("frame-{}", self.world.frame_timestamp as u64), "svg");
+        let options = self.image.clone();
+        svg::export(self, &path, &options)?;
+        Ok(path)
     }
 
-}
+    /// Writes the current frame to a new PNG file named after its time, returning the file's path.
+    pub fn export_png(&mut self) -> AppResult<PathBuf> {
+        let path = unused_path(&format!("frame-{}", self.world.frame_timestamp as u64), "png");
+        let options = self.image.clone();
+        raster::export(self, &path, &options)?;
+        Ok(path)
+    }
 
+    /// Projects the scene as seen by the camera at the time of the last frame.
+    pub fn project_scene(&mut self) {
+        self.scene.render(&self.world, &Transform::identity());
+    }
 
-struct Polyhedron {
-    polygons: Vec<Polygon>,
-}
+    /// The projected scene in drawing order, later shapes drawn over earlier ones.
+    pub fn shapes(&self) -> Vec<Shape<'_>> {
+        let mut layers: Vec<(f64, Shape)> = self.scene.polygons().into_iter()
+            .map(|p| (camera_depth(p.center(), &self.world), p))
+            .filter(|(depth, _)| *depth > self.world.near_plane) //remove things that are too close or behind the camera
+            .map(|(depth, p)| (depth, Shape::Polygon(p)))
+            .collect();
+        for cloud in self.scene.point_clouds() {
+            layers.extend(cloud.layers().iter().map(|(depth, color, points)| (*depth, Shape::Points(*color, points))));
+        }
 
+        layers.sort_by(|(a, _), (b, _)| {   // to render in the correct order
+            b.partial_cmp(a).unwrap()
+        });
 
-impl Polyhedron {
-    pub fn new(polygons: Vec<Polygon>) -> Self {
-        Polyhedron { 
-            polygons,
+        // filled polygons cover what is behind them, so those are drawn farthest first
+        match self.wireframe {
+            true => layers.into_iter().rev().map(|(_, s)| s).collect(),
+            false => layers.into_iter().map(|(_, s)| s).collect(),
         }
     }
 
-    fn as_datasets(&self) -> Vec<Dataset> {
-        self.polygons.iter().map(|p|{
-            Dataset::default()
-            .marker(symbols::Marker::Braille)
-            .graph_type(GraphType::Line)
-            .style(Style::default().fg(p.color))
-            .data(&p.projection)
-        }).collect()
+    /// Fits the drawing to a terminal of `width` by `height` cells.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.viewport = Viewport::new(width, height, self.character_ratio);
     }
 
-    async fn render(&mut self, world: WorldMetrics) {
-        let futures = async move {
-            (0..self.polygons.len()).for_each( |i | {
-                self.polygons.get_mut(i).unwrap().render(world.clone());
-            })
-        };
+    /// Renders the user interface widgets. 
+    pub fn render<B: Backend>(&mut self, frame: &mut Frame<'_, B>) {
 
-        futures.await;
-    } 
-}
+        // This is where you add new widgets.
+        // See the following resources:
+        // - https://docs.rs/tui/0.16.0/tui/widgets/index.html
+        // - https://github.com/fdehau/tui-rs/tree/v0.16.0/examples
+        self.world.frame_timestamp = self.playback.advance(self.clock.now());
 
+        // in case the terminal changed size without an event saying so, or the character ratio was changed
+        let size = frame.size();
+        let viewport = self.viewport;
+        if (size.width, size.height, self.character_ratio) != (viewport.width, viewport.height, viewport.character_ratio) {
+            self.resize(size.width, size.height);
+        }
 
-struct Surface {
-    map: Vec<Vec<Option<Point3d>>>,
-}
+        let res_x = self.viewport.width as f64;
+        let res_y = self.viewport.height as f64;
 
-impl Surface {
+        let x_left = -self.viewport.aspect_ratio;
+        let x_right = self.viewport.aspect_ratio;
 
-}
+        let x_left_label = format!("{x_left:0.2}");
+        let x_right_label = format!("{x_right:0.2}");
+        let x_middle_label = format!("{:0.2}", (x_left + x_right)/2.0);
 
-type Matrix = [[f64;3];3];
+        self.project_scene();
+        let shapes = self.shapes();
 
-struct MatrixFactory;
-impl MatrixFactory {
-    fn identity() -> Matrix {
-        [
-            [1f64, 0f64, 0f64],
-            [0f64, 1f64, 0f64],
-            [0f64, 0f64, 1f64],
-        ]
-    }
+        // fill points a little closer than the points of the marker, so that no cell is missed,
+        // leaving 5 columns and 3 rows for the title and axes
+        let (per_column, per_row) = self.renderer.resolution();
+        let step = (
+            0.75 * (x_right - x_left) / (res_x - 5.0).max(1.0) / per_column as f64,
+            0.75 * 2.0 / (res_y - 3.0).max(1.0) / per_row as f64,
+        );
+        let fills: Vec<Points2d> = shapes.iter().map(|shape| match shape {
+            Shape::Polygon(p) if !self.wireframe => p.fill(step),
+            _ => Vec::new(),
+        }).collect();
 
-    fn rotate_x(theta: Radians) -> Matrix {
-        let cos = theta.data.cos();
-        let sin = theta.data.sin();
-        [ 
-            [1f64, 0f64, 0f64],
-            [0f64, cos , -sin],
-            [0f64, sin , cos ]
-        ]
-    }
+        let marker = self.renderer.marker();
+        let points = |color: Color, points| Dataset::default()
+            .marker(marker)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(color))
+            .data(points);
+        let mut datasets: Vec<Dataset> = Vec::new();
+        for (shape, fill) in shapes.iter().zip(&fills) {
+            match shape {
+                Shape::Polygon(p) => {
+                    if !self.wireframe {
+                        datasets.push(points(p.color(), fill));
+                    }
+                    datasets.push(p.as_dataset().marker(marker));
+                }
+                Shape::Points(color, cloud) => datasets.push(points(*color, cloud)),
+            }
+        }
 
-    fn rotate_y(theta: Radians) -> Matrix {
-        let cos = theta.data.cos();
-        let sin = theta.data.sin();
-        [ 
-            [cos , 0f64, sin ],
-            [0f64, 1f64, 0f64],
-            [-sin, 0f64, cos ]
-        ]
-    }
+        let mut title = vec![match &self.reload_error {
+            Some(error) => Span::styled(format!(" 3T - {error} "), Style::default().fg(Color::Red)),
+            None => Span::from(" 3T "),
+        }];
+        match &self.message {
+            Some(Ok(message)) => title.push(Span::styled(format!("{message} "), Style::default().fg(Color::Green))),
+            Some(Err(error)) => title.push(Span::styled(format!("{error} "), Style::default().fg(Color::Red))),
+            None => {}
+        }
+        if let Some(status) = self.playback.status() {
+            title.push(Span::styled(format!("[{status}] "), Style::default().fg(Color::Yellow)));
+        }
+        let title = Spans::from(title);
 
-    fn rotate_z(theta: Radians) -> Matrix {
-        let cos = theta.data.cos();
-        let sin = theta.data.sin();
-        [ 
-            [cos , -sin, 0f64],
-            [sin ,  cos, 0f64],
-            [0f64, 0f64, 1f64]
-        ]
-    }
+        let chart = |datasets| Chart::new(datasets)
+            .block(Block::default().title(title).border_type(BorderType::Plain))
+            .style(Style::default().fg(Color::White))
+            .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)))
 
-    fn rotate(roll: Radians, pitch: Radians, yaw: Radians)  -> Matrix {
-        let sina = yaw.data.sin();
-        let cosa = yaw.data.cos();
-        let sinb = pitch.data.sin();
-        let cosb = pitch.data.cos();
-        let sing = roll.data.sin();
-        let cosg = roll.data.cos();
-
-        [ 
-            [
-                cosa*cosb, 
-                (cosa*sinb*sing) - (sina*cosg), 
-                (cosa*sinb*cosg) + (sina*sing)
-            ],
-            [
-                sina*cosb, 
-                (sina*sinb*sing) + (cosa*cosg), 
-                (sina*sinb*cosg) - (cosa*sing)
-            ],
-            [
-                -sinb, 
-                cosb*sing, 
-                cosb*cosg
-            ],
-        ]
+            .x_axis(Axis::default()
+                //.title(Span::styled("X Axis", Style::default().fg(Color::Red)))
+                .style(Style::default().fg(Color::White))
+                .bounds([x_left, x_right])
+                .labels([x_left_label, x_middle_label, x_right_label].iter().cloned().map(Span::from).collect()))
+            .y_axis(Axis::default()
+                //.title(Span::styled("Y Axis", Style::default().fg(Color::Red)))
+                .style(Style::default().fg(Color::White))
+                .bounds([-1.0, 1.0])
+                .labels(["-1.0", "0", "1.0"].iter().cloned().map(Span::from).collect()));
+
+        match self.renderer {
+            Renderer::Braille => frame.render_widget(chart(datasets), frame.size()),
+            Renderer::Ascii => frame.render_widget(Ascii(chart(datasets)), frame.size()),
+            Renderer::HalfBlock => frame.render_widget(HalfBlocks {
+                plot: Chart::new(datasets)
+                    .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)))
+                    .x_axis(Axis::default().bounds([x_left, x_right]))
+                    .y_axis(Axis::default().bounds([-1.0, 1.0])),
+                chart: chart(Vec::new()),
+            }, frame.size()),
+        }
     }
 }
 
 
-struct Radians {
-    data: f64
+/// `{stem}.{extension}`, or `{stem}-2.{extension}` and so on if that file exists,
+/// so that frames exported while paused don't overwrite each other.
+fn unused_path(stem: &str, extension: &str) -> PathBuf {
+    (1..)
+        .map(|n| match n {
+            1 => PathBuf::from(format!("{stem}.{extension}")),
+            n => PathBuf::from(format!("{stem}-{n}.{extension}")),
+        })
+        .find(|path| !path.exists())
+        .expect("some numbered file does not exist")
 }
 
-struct Degrees {
-    data: f64
+/// Modification time of a file, `None` when it can't be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
-impl From<f64> for Radians {
-    fn from(value: f64) -> Self {
-        Radians { data: value }
-    }
+/// The terminal the scene is drawn on.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Viewport {
+    /// Width in cells.
+    pub width: u16,
+    /// Height in cells.
+    pub height: u16,
+    /// Height of a cell divided by its width, which the aspect ratio is worked out for.
+    pub character_ratio: f64,
+    /// Width of the graph divided by its height; it spans x from minus this to this, and y from -1 to 1.
+    pub aspect_ratio: f64,
 }
 
-impl Into<Radians> for Degrees {
-    fn into(self) -> Radians {
-        Radians { data: self.data*PI/180f64 }
+impl Viewport {
+    /// The viewport of a terminal of `width` by `height` cells, each `character_ratio` times taller than wide.
+    pub fn new(width: u16, height: u16, character_ratio: f64) -> Self {
+        // the graph leaves 5 columns and 3 rows for the title and axes
+        let (columns, rows) = ((width as f64 - 5.0).max(1.0), (height as f64 - 3.0).max(1.0));
+        Viewport { width, height, character_ratio, aspect_ratio: columns / rows / character_ratio }
     }
 }
 
-impl Into<Degrees> for Radians {
-    fn into(self) -> Degrees {
-        Degrees { data: self.data*180f64/PI }
-    }
-}
-
-
-fn project_point(a: Point3d, e: Point3d, t: Point3d) -> Point2d {
-    let (ax, ay, az): Point3d = a; // point to be projected
-    let (ex, ey, ez): Point3d = e; // display's surface position relative to the camera position <0,0,0>
-    let (tx, ty, tz): Point3d = t; // the angles of the camera (Tait-Brian angles)
-    
-    let (cx, cy, cz): Point3d = (tx.cos(),ty.cos(),tz.cos()); //not to be confused with the position of the camera (ccx, ccy, ccz)
-    let (sx, sy, sz): Point3d = (tx.sin(),ty.sin(),tz.sin());
-
-    let (ccx, ccy, ccz): Point3d = (0.0,0.0,0.0); // add this as a parameter later if necessary. It's only added to the code for easy migration.
-    
-    let x = ax-ccx;
-    let y = ay-ccy;
-    let z = az-ccz;
-
-    let dx = cy * ((sz*y) + (cz*x)) - (sy*z);
-    let dy = sx * ( (cy*z) + sy*( (sz*y) + (cz*x) ) ) + cx*( (cz*y) - (sz*x) );
-    let dz = cx * ( (cy*z) + sy*( (sz*y) + (cz*x) ) ) - sx*( (cz*y) - (sz*x) );
-
-    let bx = (ez/dz)*dx + ex;
-    let by = (ez/dz)*dy + ey;
-    
-    (bx,by) // projected point
-}
-
 #[derive(Debug,Clone)]
 pub struct WorldMetrics {
     pub camera_pitch: f64,
@@ -637,7 +442,14 @@ pub struct WorldMetrics {
     pub world_translation_x: f64,
     pub world_translation_y: f64,
     pub world_translation_z: f64,
+    /// Time of the frame, in milliseconds, from the clock and playback of the [`App`].
     pub frame_timestamp: f64,
+    /// Vertical field of view, in radians.
+    pub fov: f64,
+    /// Anything closer to the camera than this is not drawn.
+    pub near_plane: f64,
+    /// Points along each side of a polygon.
+    pub line_points: usize,
 
 }
 
@@ -650,22 +462,30 @@ impl Default for WorldMetrics {
             world_translation_x: 0.0,
             world_translation_y: 0.0,
             world_translation_z: 0.0,
-            frame_timestamp: Self::get_timestamp(),
+            frame_timestamp: 0.0,
+            fov: PI/2.0, // 90deg
+            near_plane: NEAR_PLANE,
+            line_points: LINE_POINTS,
         }
     }
 
 }
 
 impl WorldMetrics {
-    pub fn get_timestamp() -> f64 {
-        SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("time went backwards!")
-        .as_millis() 
-        as f64
+    /// Where the camera is; the world is moved the other way.
+    pub fn camera_position(&self) -> Point3d {
+        (-self.world_translation_x, -self.world_translation_y, -self.world_translation_z)
+    }
+
+    pub fn set_camera_position(&mut self, (x, y, z): Point3d) {
+        self.world_translation_x = -x;
+        self.world_translation_y = -y;
+        self.world_translation_z = -z;
     }
 
-    pub fn update_timestamp(&mut self) {
-        self.frame_timestamp = Self::get_timestamp();
+    /// Unit vector the camera looks along, the direction that projects to the middle of the screen.
+    pub fn camera_direction(&self) -> Point3d {
+        let (pitch, yaw) = (self.camera_pitch, self.camera_yaw);
+        (yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos())
     }
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use serde_json::json;
+use tui::backend::{Backend, CrosstermBackend};
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use crate::app::AppResult;
+
+/// Records drawn frames, with the time they were drawn, as an asciinema v2 cast.
+///
+/// Each frame is written as the escape codes that turn the previous frame into it.
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+    /// The last frame written, to draw only the cells that changed.
+    previous: Option<Buffer>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Starts recording to a `.cast` file for a terminal of `width` by `height` cells.
+    pub fn create(path: impl AsRef<Path>, width: u16, height: u16) -> AppResult<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Recorder::new(BufWriter::new(file), width, height)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts recording to `writer`, beginning with the header of the cast.
+    pub fn new(mut writer: W, width: u16, height: u16) -> AppResult<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_secs()).unwrap_or(0);
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "env": { "TERM": std::env::var("TERM").unwrap_or_else(|_| "xterm-256color".into()) },
+        });
+        writeln!(writer, "{header}")?;
+        writer.flush()?;
+        Ok(Recorder { writer, start: Instant::now(), previous: None })
+    }
+
+    /// Adds a drawn frame; a frame of another size is recorded as a resize and drawn in full.
+    pub fn frame(&mut self, buffer: &Buffer) -> AppResult<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        let mut output = Vec::new();
+        let previous = match self.previous.take() {
+            Some(previous) if previous.area == buffer.area => previous,
+            previous => {
+                if previous.is_some() {
+                    let Rect { width, height, .. } = buffer.area;
+                    self.event(time, "r", &format!("{width}x{height}"))?;
+                }
+                // hide the cursor and clear the screen, then draw every cell
+                output.extend_from_slice(b"\x1b[?25l\x1b[2J");
+                Buffer::empty(buffer.area)
+            }
+        };
+        CrosstermBackend::new(&mut output).draw(previous.diff(buffer).into_iter())?;
+        self.event(time, "o", &String::from_utf8_lossy(&output))?;
+        self.writer.flush()?;
+        self.previous = Some(buffer.clone());
+        Ok(())
+    }
+
+    fn event(&mut self, time: f64, code: &str, data: &str) -> AppResult<()> {
+        writeln!(self.writer, "{}", json!([(time * 1e6).round() / 1e6, code, data]))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+/// Height of a terminal cell divided by its width, if the terminal tells its size in pixels.
+///
+/// The size is asked of the terminal device first, and then of the terminal itself with
+/// escape sequences, so this is best called before anything else reads the terminal's input.
+pub fn character_ratio() -> Option<f64> {
+    #[cfg(unix)]
+    {
+        unix::from_window_size().or_else(unix::from_escape_queries)
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// The ratio of cells `(width, height)` pixels large, if that is a sensible cell.
+fn ratio(width: f64, height: f64) -> Option<f64> {
+    let ratio = height / width;
+    (ratio.is_finite() && (0.25..=8.0).contains(&ratio)).then_some(ratio)
+}
+
+/// Parses the replies to `CSI 16 t` (`CSI 6 ; height ; width t`, the size of a cell) and
+/// `CSI 14 t` (`CSI 4 ; height ; width t`, the size of the text area of `columns` by `rows` cells).
+///
+/// The size of a cell wins over that of the text area; anything else in `replies` is skipped.
+pub fn parse_replies(replies: &str, (columns, rows): (u16, u16)) -> Option<f64> {
+    let mut text_area = None;
+    for reply in replies.split("\x1b[").filter_map(|r| r.split_once('t').map(|(r, _)| r)) {
+        let numbers: Vec<u32> = match reply.split(';').map(|n| n.parse()).collect() {
+            Ok(numbers) => numbers,
+            Err(_) => continue,
+        };
+        match numbers[..] {
+            [6, height, width] => return ratio(width as f64, height as f64),
+            [4, height, width] if columns > 0 && rows > 0 => {
+                text_area = ratio(width as f64 / columns as f64, height as f64 / rows as f64);
+            }
+            _ => {}
+        }
+    }
+    text_area
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::{Duration, Instant};
+    use crossterm::terminal;
+
+    /// How long to wait for a terminal that may never answer.
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    /// From the pixel size the terminal device reports (`TIOCGWINSZ`), which many terminals leave at 0.
+    pub fn from_window_size() -> Option<f64> {
+        [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO].into_iter().find_map(|fd| {
+            // SAFETY: TIOCGWINSZ only writes a winsize into the one given
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 || size.ws_row == 0 {
+                return None;
+            }
+            super::ratio(
+                size.ws_xpixel as f64 / size.ws_col as f64,
+                size.ws_ypixel as f64 / size.ws_row as f64,
+            )
+        })
+    }
+
+    /// From the replies to the `CSI 16 t` and `CSI 14 t` queries.
+    ///
+    /// A device attributes query (`CSI c`) follows them, which every terminal answers,
+    /// so there is no waiting for replies that will never come.
+    pub fn from_escape_queries() -> Option<f64> {
+        let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+        let raw = terminal::is_raw_mode_enabled().ok()?;
+        if !raw {
+            terminal::enable_raw_mode().ok()?;
+        }
+        let replies = tty.write_all(b"\x1b[16t\x1b[14t\x1b[c").and_then(|_| tty.flush()).ok().map(|_| read_replies(&mut tty));
+        if !raw {
+            let _ = terminal::disable_raw_mode();
+        }
+        super::parse_replies(&replies?, terminal::size().ok()?)
+    }
+
+    /// Reads from the terminal until the reply to the device attributes query, or the timeout.
+    fn read_replies(tty: &mut std::fs::File) -> String {
+        let start = Instant::now();
+        let mut replies = Vec::new();
+        let mut buffer = [0; 256];
+        while let Some(left) = TIMEOUT.checked_sub(start.elapsed()) {
+            let mut poll = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+            // SAFETY: poll reads and writes only the one pollfd given
+            if unsafe { libc::poll(&mut poll, 1, left.as_millis() as libc::c_int) } <= 0 {
+                break;
+            }
+            match tty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => replies.extend_from_slice(&buffer[..n]),
+            }
+            let text = String::from_utf8_lossy(&replies);
+            if text.contains("\x1b[?") && text.ends_with('c') {
+                break;
+            }
+        }
+        String::from_utf8_lossy(&replies).into_owned()
+    }
+}
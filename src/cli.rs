@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+use clap::Parser;
+use crate::app::{App, AppResult, CameraMode};
+use crate::color::parse_color;
+use crate::config::Config;
+use crate::render::Renderer;
+
+/// Terminal viewer for 3D levels, models and point clouds.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Level (.json), model (.ply, .gltf) or point cloud (.csv, .xyz, .txt) to show, instead of the demo scene.
+    pub path: Option<PathBuf>,
+
+    /// Config file to read instead of dddragon/config.toml in the XDG config directory.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Vertical field of view, in degrees [default: 90].
+    #[arg(long)]
+    pub fov: Option<f64>,
+
+    /// Height of a terminal cell divided by its width, instead of asking the terminal [default: 1.8].
+    #[arg(long)]
+    pub character_ratio: Option<f64>,
+
+    /// Points along each side of a polygon [default: 200].
+    #[arg(long)]
+    pub line_points: Option<usize>,
+
+    /// Anything closer to the camera than this is not drawn [default: 1].
+    #[arg(long)]
+    pub near_plane: Option<f64>,
+
+    /// How points are drawn: braille, ascii or halfblock.
+    #[arg(long, default_value = "braille")]
+    pub renderer: Renderer,
+
+    /// Milliseconds between frames [default: 20].
+    #[arg(long)]
+    pub tick_rate: Option<u64>,
+
+    /// How the movement keys move the camera: fly or orbit.
+    #[arg(long, default_value = "fly")]
+    pub camera: CameraMode,
+
+    /// Draw polygon outlines only (the default).
+    #[arg(long, conflicts_with = "solid")]
+    pub wireframe: bool,
+
+    /// Fill polygons, hiding what is behind them.
+    #[arg(long)]
+    pub solid: bool,
+
+    /// Print a single frame to stdout instead of opening the terminal interface.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Time of the frame drawn by --headless, --svg and --image, in seconds, which poses animated objects.
+    #[arg(long, default_value_t = 0.0)]
+    pub time: f64,
+
+    /// Size of the headless frame, as WIDTHxHEIGHT.
+    #[arg(long, default_value = "80x24", value_parser = parse_size)]
+    pub size: (u16, u16),
+
+    /// Write a single frame to this SVG file instead of opening the terminal interface.
+    #[arg(long, value_name = "PATH")]
+    pub svg: Option<PathBuf>,
+
+    /// Write a single frame to this PNG (.png) or PPM (.ppm) image instead of opening the terminal interface.
+    #[arg(long, value_name = "PATH")]
+    pub image: Option<PathBuf>,
+
+    /// Record the session to an asciinema (.cast) file.
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Record the keys, mouse, resizes and ticks of the session, with their timing, to this file,
+    /// along with the terminal's size and character ratio.
+    #[arg(long, value_name = "PATH")]
+    pub record_input: Option<PathBuf>,
+
+    /// Replay input recorded with --record-input instead of reading the terminal,
+    /// drawn at the recorded size and character ratio.
+    #[arg(long, value_name = "PATH")]
+    pub replay: Option<PathBuf>,
+
+    /// Size of exported images, in pixels, as WIDTHxHEIGHT.
+    #[arg(long, default_value = "1200x800", value_parser = parse_size)]
+    pub image_size: (u16, u16),
+
+    /// Width of lines in exported images, in pixels.
+    #[arg(long, default_value_t = 1.5)]
+    pub line_width: f64,
+
+    /// Background of exported images: a colour name, #rrggbb, or none.
+    #[arg(long, default_value = "black")]
+    pub background: String,
+}
+
+impl Args {
+    /// Reads the config file, with the settings given on the command line in place of its own.
+    pub fn config(&self) -> AppResult<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::load_default()?,
+        };
+        let display = &mut config.display;
+        display.fov = self.fov.unwrap_or(display.fov);
+        display.character_ratio = self.character_ratio.unwrap_or(display.character_ratio);
+        display.line_points = self.line_points.unwrap_or(display.line_points);
+        display.near_plane = self.near_plane.unwrap_or(display.near_plane);
+        display.tick_rate = self.tick_rate.unwrap_or(display.tick_rate);
+        display.validate(|field| format!("--{}", field.replace('_', "-")))?;
+        Ok(config)
+    }
+
+    /// Opens the file to show, and sets up the camera and drawing of `app` from `config` and the command line.
+    pub fn apply(&self, app: &mut App, config: &Config) -> AppResult<()> {
+        app.keymap = config.keymap()?;
+        app.character_ratio = config.display.character_ratio;
+        app.world.fov = config.display.fov.to_radians();
+        app.world.line_points = config.display.line_points;
+        app.world.near_plane = config.display.near_plane;
+        if !self.time.is_finite() {
+            return Err(format!("--time must be a number of seconds, not {}", self.time).into());
+        }
+        if let Some(path) = &self.path {
+            app.open(path)?;
+        }
+        app.renderer = self.renderer;
+        app.wireframe = !self.solid;
+        app.set_camera_mode(self.camera);
+        if self.line_width.is_nan() || self.line_width <= 0.0 {
+            return Err(format!("--line-width must be more than 0, not {}", self.line_width).into());
+        }
+        app.image.width = self.image_size.0 as u32;
+        app.image.height = self.image_size.1 as u32;
+        app.image.line_width = self.line_width;
+        app.image.background = match self.background.as_str() {
+            "none" => None,
+            color => Some(parse_color(color).map_err(|e| format!("--background: {e}"))?),
+        };
+        Ok(())
+    }
+}
+
+fn parse_size(size: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("invalid size '{size}', expected WIDTHxHEIGHT such as 80x24");
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
+
@@ -0,0 +1,157 @@
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the time frames are drawn at, which drives animations.
+pub trait Clock: Debug {
+    /// The time of a new frame, in milliseconds.
+    fn now(&mut self) -> f64;
+}
+
+/// The time of day, in milliseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> f64 {
+        // a clock set before the epoch gives 0 rather than failing
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as f64
+    }
+}
+
+/// A clock that moves on by the same step every frame, whatever the real time.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedStep {
+    /// Time of the next frame, in milliseconds.
+    pub time: f64,
+    /// Milliseconds between frames; with 0 the time stands still.
+    pub step: f64,
+}
+
+impl FixedStep {
+    pub fn new(start: f64, step: f64) -> Self {
+        FixedStep { time: start, step }
+    }
+}
+
+impl Clock for FixedStep {
+    fn now(&mut self) -> f64 {
+        let time = self.time;
+        self.time += self.step;
+        time
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so one can be given to an [`App`](crate::app::App) and the other kept to set it.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    time: Rc<Cell<f64>>,
+}
+
+impl ManualClock {
+    pub fn new(time: f64) -> Self {
+        ManualClock { time: Rc::new(Cell::new(time)) }
+    }
+
+    /// Sets the time, in milliseconds.
+    pub fn set(&self, time: f64) {
+        self.time.set(time);
+    }
+
+    /// Moves the time on by `duration` milliseconds.
+    pub fn advance(&self, duration: f64) {
+        self.time.set(self.time.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&mut self) -> f64 {
+        self.time.get()
+    }
+}
+
+/// Speeds [`Playback::faster`] and [`Playback::slower`] move between.
+pub const TIME_SCALES: [f64; 7] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0];
+
+/// Simulation time, which follows a clock at some speed, or stands still while paused.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    /// Whether simulation time stands still.
+    pub paused: bool,
+    /// How many times faster than the clock simulation time passes.
+    scale: f64,
+    /// Time of the simulation, in milliseconds.
+    time: f64,
+    /// Clock time of the last frame.
+    last: Option<f64>,
+    /// Whether to move on one frame while paused.
+    step: bool,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback { paused: false, scale: 1.0, time: 0.0, last: None, step: false }
+    }
+}
+
+impl Playback {
+    /// The simulation time of a frame drawn at clock time `now`; the first frame starts at the clock's time.
+    pub fn advance(&mut self, now: f64) -> f64 {
+        match self.last {
+            None => self.time = now,
+            Some(last) if !self.paused || self.step => self.time += (now - last) * self.scale,
+            Some(_) => {}
+        }
+        self.last = Some(now);
+        self.step = false;
+        self.time
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pauses, and moves on by one frame at the next frame.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step = true;
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets the speed, kept between the slowest and fastest of [`TIME_SCALES`].
+    pub fn set_scale(&mut self, scale: f64) {
+        if !scale.is_nan() {
+            self.scale = scale.clamp(TIME_SCALES[0], TIME_SCALES[TIME_SCALES.len() - 1]);
+        }
+    }
+
+    /// Moves up to the next faster of [`TIME_SCALES`].
+    pub fn faster(&mut self) {
+        if let Some(scale) = TIME_SCALES.iter().find(|s| **s > self.scale) {
+            self.scale = *scale;
+        }
+    }
+
+    /// Moves down to the next slower of [`TIME_SCALES`].
+    pub fn slower(&mut self) {
+        if let Some(scale) = TIME_SCALES.iter().rev().find(|s| **s < self.scale) {
+            self.scale = *scale;
+        }
+    }
+
+    /// What to show about the playback, if it isn't running at normal speed.
+    pub fn status(&self) -> Option<String> {
+        match (self.paused, self.scale == 1.0) {
+            (false, true) => None,
+            (false, false) => Some(format!("{}x", self.scale)),
+            (true, true) => Some("paused".to_string()),
+            (true, false) => Some(format!("paused {}x", self.scale)),
+        }
+    }
+}
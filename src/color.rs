@@ -0,0 +1,97 @@
+use tui::style::Color;
+use crate::app::AppResult;
+use crate::pointcloud::Rgb;
+
+const NAMES: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("dark_gray", Color::DarkGray),
+    ("light_red", Color::LightRed),
+    ("light_green", Color::LightGreen),
+    ("light_yellow", Color::LightYellow),
+    ("light_blue", Color::LightBlue),
+    ("light_magenta", Color::LightMagenta),
+    ("light_cyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+/// Reads a colour name such as `light_yellow`, or a hex colour such as `#ff8800`.
+pub fn parse_color(name: &str) -> AppResult<Color> {
+    let name = name.trim();
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or(""), 16);
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Ok(r), Ok(g), Ok(b)) => Ok(Color::Rgb(r, g, b)),
+            _ => Err(format!("invalid colour '{name}', expected #rrggbb").into()),
+        };
+    }
+    let key = name.to_lowercase().replace(['-', ' '], "_");
+    NAMES.iter()
+        .find(|(n, _)| *n == key || n.replace('_', "") == key)
+        .map(|(_, c)| *c)
+        .ok_or_else(|| format!("unknown colour '{name}'").into())
+}
+
+/// The name [`parse_color`] reads back as `color`.
+pub fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Indexed(i) => {
+            let (r, g, b) = to_rgb(Color::Indexed(i));
+            format!("#{r:02x}{g:02x}{b:02x}")
+        }
+        named => NAMES.iter().find(|(_, c)| *c == named).map(|(n, _)| n.to_string()).unwrap_or_default(),
+    }
+}
+
+/// Approximate red, green and blue of a terminal colour, using the common xterm palette.
+pub fn to_rgb(color: Color) -> Rgb {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Reset | Color::White => (255, 255, 255),
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::Indexed(i) => indexed(i),
+    }
+}
+
+fn indexed(i: u8) -> Rgb {
+    const BASE: [Color; 16] = [
+        Color::Black, Color::Red, Color::Green, Color::Yellow,
+        Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+        Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow,
+        Color::LightBlue, Color::LightMagenta, Color::LightCyan, Color::White,
+    ];
+    match i {
+        0..=15 => to_rgb(BASE[i as usize]),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + (v * 40) };
+            let i = i - 16;
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        _ => {
+            let v = 8 + ((i - 232) * 10);
+            (v, v, v)
+        }
+    }
+}
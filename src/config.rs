@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::app::{AppResult, CHARACTER_RATIO};
+use crate::keymap::{Action, KeyBinding, Keymap, Sensitivity};
+use crate::scene::{LINE_POINTS, NEAR_PLANE};
+
+/// Settings read from the user's config file.
+///
+/// ```toml
+/// [display]
+/// character_ratio = 2.0
+/// fov = 75
+///
+/// [keys]
+/// sensitivity = { move = 0.1, turn = 0.03, zoom = 0.05 }
+///
+/// [keys.bindings]
+/// quit = ["esc", "q", "ctrl+c"]
+/// move_forward = ["w", "shift+up"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub display: Display,
+    pub keys: Keys,
+}
+
+/// The `[display]` table: how the scene is drawn.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Display {
+    /// Height of a terminal cell divided by its width, when the terminal doesn't tell its size in pixels.
+    pub character_ratio: f64,
+    /// Vertical field of view, in degrees.
+    pub fov: f64,
+    /// Points along each side of a polygon.
+    pub line_points: usize,
+    /// Milliseconds between frames.
+    pub tick_rate: u64,
+    /// Anything closer to the camera than this is not drawn.
+    pub near_plane: f64,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display {
+            character_ratio: CHARACTER_RATIO,
+            fov: 90.0,
+            line_points: LINE_POINTS,
+            tick_rate: 20,
+            near_plane: NEAR_PLANE,
+        }
+    }
+}
+
+impl Display {
+    /// Checks that every setting makes sense, naming a bad one with `name`.
+    pub fn validate(&self, name: impl Fn(&str) -> String) -> Result<(), String> {
+        let positive = |field: &str, value: f64| match value.is_finite() && value > 0.0 {
+            true => Ok(()),
+            false => Err(format!("{} must be more than 0, not {value}", name(field))),
+        };
+        positive("character_ratio", self.character_ratio)?;
+        positive("near_plane", self.near_plane)?;
+        if !(self.fov > 0.0 && self.fov < 180.0) {
+            return Err(format!("{} must be between 0 and 180 degrees, not {}", name("fov"), self.fov));
+        }
+        if !(1..=100_000).contains(&self.line_points) {
+            return Err(format!("{} must be between 1 and 100000, not {}", name("line_points"), self.line_points));
+        }
+        if self.tick_rate == 0 {
+            return Err(format!("{} must be at least 1 millisecond", name("tick_rate")));
+        }
+        Ok(())
+    }
+}
+
+/// The `[keys]` table; actions not in `bindings` keep their default keys.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub bindings: HashMap<Action, Vec<KeyBinding>>,
+    pub sensitivity: Sensitivity,
+}
+
+impl Config {
+    /// Where the config file is looked for: `dddragon/config.toml` in the XDG config directory.
+    pub fn path() -> Option<PathBuf> {
+        let directory = match env::var_os("XDG_CONFIG_HOME") {
+            Some(directory) if Path::new(&directory).is_absolute() => PathBuf::from(directory),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(directory.join("dddragon").join("config.toml"))
+    }
+
+    /// Reads the config file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let config: Config = toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+        config.validate().map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Reads the config file from its usual [`path`](Config::path), or the defaults if there is none.
+    pub fn load_default() -> AppResult<Self> {
+        match Config::path() {
+            Some(path) if path.exists() => Config::load(path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.display.validate(|field| format!("display.{field}"))?;
+        let Sensitivity { movement, turn, zoom } = self.keys.sensitivity;
+        for (name, value) in [("move", movement), ("turn", turn), ("zoom", zoom)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("keys.sensitivity.{name} must be more than 0, not {value}"));
+            }
+        }
+        if zoom >= 1.0 {
+            return Err(format!("keys.sensitivity.zoom must be less than 1, not {zoom}"));
+        }
+        self.keymap().map(|_| ())
+    }
+
+    /// The keys described by the `[keys]` table.
+    pub fn keymap(&self) -> Result<Keymap, String> {
+        Keymap::new(&self.keys.bindings, self.keys.sensitivity)
+    }
+}
@@ -1,11 +1,17 @@
 use crate::app::AppResult;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
-use std::sync::mpsc;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -15,6 +21,12 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// A producer failed and ends the main loop, e.g. the terminal could not be read.
+    ///
+    /// The terminal sends no more events after one; other producers post theirs through [`EventHandler::sender`].
+    Error(String),
+    /// A message from another producer, such as a timer or file watcher, shown in the title.
+    Custom(String),
 
     Default,
 }
@@ -26,8 +38,24 @@ pub struct EventHandler {
     sender: mpsc::Sender<Event>,
     /// Event receiver channel.
     receiver: mpsc::Receiver<Event>,
-    /// Event handler thread.
-    handler: thread::JoinHandle<()>,
+    /// Event handler thread, until it is stopped.
+    handler: Option<thread::JoinHandle<()>>,
+    /// Whether the handler thread should keep going.
+    running: Arc<AtomicBool>,
+    /// File the received events are written to, and when recording started.
+    recording: RefCell<Option<(BufWriter<File>, Instant)>>,
+}
+
+/// An event and the time, in seconds from the start of a recording, it was received.
+pub type TimedEvent = (f64, Event);
+
+/// The terminal a recording was made in, written at the top of the recording so that a replay draws the same frames.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TerminalSetup {
+    /// Width and height in cells when recording started.
+    pub size: (u16, u16),
+    /// Height of a terminal cell divided by its width.
+    pub character_ratio: f64,
 }
 
 impl EventHandler {
@@ -35,44 +63,147 @@ impl EventHandler {
     pub fn new(tick_rate: u64) -> Self {
         let tick_rate = Duration::from_millis(tick_rate);
         let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
         let handler = {
-            let sender = sender.clone();
+            let (sender, running) = (sender.clone(), running.clone());
+            thread::spawn(move || read_terminal(sender, tick_rate, &running))
+        };
+        Self {
+            sender,
+            receiver,
+            handler: Some(handler),
+            running,
+            recording: RefCell::new(None),
+        }
+    }
+
+    /// Constructs an [`EventHandler`] that replays recorded events, at the times they were recorded,
+    /// instead of reading the terminal.
+    ///
+    /// Once the recording runs out, events are read from the terminal again.
+    pub fn replay(events: Vec<TimedEvent>, tick_rate: u64) -> Self {
+        let tick_rate = Duration::from_millis(tick_rate);
+        let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let handler = {
+            let (sender, running) = (sender.clone(), running.clone());
             thread::spawn(move || {
-                let mut last_tick = Instant::now();
-                loop {
-                    let timeout = tick_rate
-                        .checked_sub(last_tick.elapsed())
-                        .unwrap_or(tick_rate);
-
-                    if event::poll(timeout).expect("no events available") {
-                        match event::read().expect("unable to read event") {
-                            CrosstermEvent::Key(e) => sender.send(Event::Key(e)),
-                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
-                            CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            _ => sender.send(Event::Default)
+                let start = Instant::now();
+                for (time, event) in events {
+                    let due = Duration::from_secs_f64(time.max(0.0));
+                    // in short naps, so that stopping doesn't wait for a distant event
+                    while let Some(left) = due.checked_sub(start.elapsed()).filter(|left| !left.is_zero()) {
+                        if !running.load(Ordering::Relaxed) {
+                            return;
                         }
-                        .expect("failed to send terminal event")
+                        thread::sleep(left.min(tick_rate));
                     }
-
-                    if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
-                        last_tick = Instant::now();
+                    if !running.load(Ordering::Relaxed) || sender.send(event).is_err() {
+                        return;
                     }
                 }
+                read_terminal(sender, tick_rate, &running)
             })
         };
         Self {
             sender,
             receiver,
-            handler,
+            handler: Some(handler),
+            running,
+            recording: RefCell::new(None),
         }
     }
 
+    /// A sender through which other producers, such as timers or file watchers,
+    /// can post events that [`EventHandler::next`] receives along with the terminal's.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
+    /// Stops the handler thread and waits for it to finish, which takes at most a tick.
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handler) = self.handler.take() {
+            let _ = handler.join();
+        }
+    }
+
+    /// Writes `setup`, and then every event received from now on with its time, to a file that [`load`] reads back.
+    pub fn record(&mut self, path: impl AsRef<Path>, setup: TerminalSetup) -> AppResult<()> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", serde_json::to_string(&setup)?)?;
+        writer.flush()?;
+        self.recording = RefCell::new(Some((writer, Instant::now())));
+        Ok(())
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
     /// there is no data available and it's possible for more data to be sent.
     pub fn next(&self) -> AppResult<Event> {
-        Ok(self.receiver.recv()?)
+        let event = self.receiver.recv()?;
+        if let Some((writer, start)) = self.recording.borrow_mut().as_mut() {
+            let time = (start.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+            writeln!(writer, "{}", serde_json::to_string(&(time, &event))?)?;
+            writer.flush()?;
+        }
+        Ok(event)
+    }
+}
+
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Reads a recording made by [`EventHandler::record`]: the terminal it was made in, and then the events, one per line.
+pub fn load(path: impl AsRef<Path>) -> AppResult<(TerminalSetup, Vec<TimedEvent>)> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let (i, line) = lines.next().ok_or_else(|| format!("{}: the recording is empty", path.display()))?;
+    let setup = serde_json::from_str(line).map_err(|e| format!("{}:{}: {e}", path.display(), i + 1))?;
+    let events = lines.map(|(i, line)| {
+        serde_json::from_str(line).map_err(|e| format!("{}:{}: {e}", path.display(), i + 1).into())
+    }).collect::<AppResult<_>>()?;
+    Ok((setup, events))
+}
+
+/// Sends terminal events, and a tick every `tick_rate`, until stopped or the receiver is gone.
+///
+/// If the terminal can't be read, that is sent as an [`Event::Error`] and the thread ends.
+fn read_terminal(sender: mpsc::Sender<Event>, tick_rate: Duration, running: &AtomicBool) {
+    let mut last_tick = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        let timeout = tick_rate
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or(tick_rate);
+
+        let read = event::poll(timeout).and_then(|ready| ready.then(event::read).transpose());
+        let sent = match read {
+            Ok(None) => Ok(()),
+            Ok(Some(CrosstermEvent::Key(e))) => sender.send(Event::Key(e)),
+            Ok(Some(CrosstermEvent::Mouse(e))) => sender.send(Event::Mouse(e)),
+            Ok(Some(CrosstermEvent::Resize(w, h))) => sender.send(Event::Resize(w, h)),
+            Ok(Some(_)) => sender.send(Event::Default),
+            Err(e) => {
+                let _ = sender.send(Event::Error(format!("unable to read terminal events: {e}")));
+                return;
+            }
+        };
+        if sent.is_err() {
+            return;
+        }
+
+        if last_tick.elapsed() >= tick_rate {
+            if sender.send(Event::Tick).is_err() {
+                return;
+            }
+            last_tick = Instant::now();
+        }
     }
 }
@@ -0,0 +1,304 @@
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
+use tui::style::Color;
+use crate::app::AppResult;
+use crate::pointcloud::PointCloud;
+use crate::quaternions::Quaternion;
+use crate::scene::{Point3d, Points3d, Polygon, Polyhedron, Transform};
+
+/// Colour of primitives without a material, the glTF default of white.
+const DEFAULT_COLOR: (u8, u8, u8) = (255, 255, 255);
+
+/// Reads a glTF 2.0 `.gltf` file into a scene graph node holding the default scene.
+///
+/// Buffers may be embedded as base64 `data:` URIs or stored in files next to the `.gltf`; sparse accessors are not supported.
+/// Every glTF node becomes a [`Polyhedron`] with the node's translation and rotation.
+/// [`Transform`] has no scale, so a node's scale, with those of its ancestors, is applied to its mesh
+/// and to its children's translations instead; this is exact unless a non-uniform scale sits above a rotation.
+/// Faces are coloured with their material's base colour.
+pub fn load(path: impl AsRef<Path>) -> AppResult<Polyhedron> {
+    let path = path.as_ref();
+    let document: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Gltf::new(&document, path.parent())?.scene()
+}
+
+/// Parses a glTF 2.0 document whose buffers are all embedded.
+pub fn parse(json: &str) -> AppResult<Polyhedron> {
+    let document: Value = serde_json::from_str(json)?;
+    Gltf::new(&document, None)?.scene()
+}
+
+struct Gltf<'a> {
+    document: &'a Value,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl<'a> Gltf<'a> {
+    fn new(document: &'a Value, directory: Option<&Path>) -> AppResult<Self> {
+        let version = document["asset"]["version"].as_str().unwrap_or("");
+        if !version.starts_with("2.") {
+            return Err(format!("unsupported glTF version '{version}'").into());
+        }
+        let buffers = array(&document["buffers"]).iter().map(|buffer| {
+            let uri = buffer["uri"].as_str().ok_or("glTF buffer has no uri")?;
+            match (uri.strip_prefix("data:"), directory) {
+                (Some(data), _) => {
+                    let (_, encoded) = data.split_once(";base64,").ok_or("glTF data URI is not base64")?;
+                    base64(encoded)
+                }
+                (None, Some(directory)) => Ok(fs::read(directory.join(uri))?),
+                (None, None) => Err(format!("glTF buffer '{uri}' is not embedded").into()),
+            }
+        }).collect::<AppResult<_>>()?;
+        Ok(Gltf { document, buffers })
+    }
+
+    fn scene(&self) -> AppResult<Polyhedron> {
+        let scenes = array(&self.document["scenes"]);
+        let roots: Vec<usize> = match scenes.get(self.document["scene"].as_u64().unwrap_or(0) as usize) {
+            Some(scene) => indices(&scene["nodes"]),
+            // without scenes, every node that is nobody's child is a root
+            None => {
+                let nodes = array(&self.document["nodes"]);
+                let children: Vec<usize> = nodes.iter().flat_map(|n| indices(&n["children"])).collect();
+                (0..nodes.len()).filter(|i| !children.contains(i)).collect()
+            }
+        };
+        let children = roots.into_iter().map(|n| self.node(n, 0, (1.0, 1.0, 1.0))).collect::<AppResult<_>>()?;
+        Ok(Polyhedron::group(children))
+    }
+
+    /// Builds a node whose ancestors are scaled by `inherited` altogether.
+    fn node(&self, index: usize, depth: usize, inherited: Point3d) -> AppResult<Polyhedron> {
+        if depth > 64 {
+            return Err("glTF node hierarchy is too deep or cyclic".into());
+        }
+        let node = self.get("nodes", index)?;
+
+        let (mut transform, (sx, sy, sz)) = match node["matrix"].as_array() {
+            Some(m) => decompose(&m.iter().filter_map(Value::as_f64).collect::<Vec<_>>())?,
+            None => {
+                let t = numbers(&node["translation"], &[0.0, 0.0, 0.0]);
+                let r = numbers(&node["rotation"], &[0.0, 0.0, 0.0, 1.0]);
+                let s = numbers(&node["scale"], &[1.0, 1.0, 1.0]);
+                (
+                    Transform::new((t[0], t[1], t[2]), Quaternion::new(r[3], r[0], r[1], r[2])),
+                    (s[0], s[1], s[2]),
+                )
+            }
+        };
+
+        // the translation is in the ancestors' scaled space, and the mesh in this node's as well
+        let (ix, iy, iz) = inherited;
+        let (tx, ty, tz) = transform.translation;
+        transform.translation = (tx * ix, ty * iy, tz * iz);
+        let scale = (sx * ix, sy * iy, sz * iz);
+
+        let mut result = match node["mesh"].as_u64() {
+            Some(mesh) => self.mesh(mesh as usize, scale)?,
+            None => Polyhedron::group(Vec::new()),
+        };
+        for child in indices(&node["children"]) {
+            result = result.with_child(self.node(child, depth + 1, scale)?);
+        }
+        Ok(result.with_transform(transform))
+    }
+
+    fn mesh(&self, index: usize, (sx, sy, sz): Point3d) -> AppResult<Polyhedron> {
+        let mut polygons: Vec<Polygon> = Vec::new();
+        let mut clouds: Vec<PointCloud> = Vec::new();
+        for primitive in array(&self.get("meshes", index)?["primitives"]) {
+            let position = primitive["attributes"]["POSITION"].as_u64().ok_or("glTF primitive has no POSITION")?;
+            let vertices: Points3d = self.accessor(position as usize)?.iter()
+                .map(|v| (v[0] * sx, v[1] * sy, v[2] * sz))
+                .collect();
+            let order: Vec<usize> = match primitive["indices"].as_u64() {
+                Some(accessor) => self.accessor(accessor as usize)?.iter().map(|i| i[0] as usize).collect(),
+                None => (0..vertices.len()).collect(),
+            };
+            if let Some(&i) = order.iter().find(|&&i| i >= vertices.len()) {
+                return Err(format!("glTF index {i} is out of range").into());
+            }
+            let (r, g, b) = self.base_color(&primitive["material"]);
+            let color = Color::Rgb(r, g, b);
+            let corners = |face: &[usize]| -> Points3d { face.iter().map(|&i| vertices[order[i]]).collect() };
+
+            let n = order.len();
+            let faces: Vec<(Vec<usize>, bool)> = match primitive["mode"].as_u64().unwrap_or(4) {
+                0 => {
+                    clouds.push(PointCloud::new(order.iter().map(|&i| vertices[i]).collect(), (r, g, b)));
+                    Vec::new()
+                }
+                1 => (0..n / 2).map(|i| (vec![2 * i, 2 * i + 1], false)).collect(),
+                2 => vec![((0..n).collect(), true)],
+                3 => vec![((0..n).collect(), false)],
+                4 => (0..n / 3).map(|i| (vec![3 * i, 3 * i + 1, 3 * i + 2], true)).collect(),
+                5 => (2..n).map(|i| (if i % 2 == 0 { vec![i - 2, i - 1, i] } else { vec![i - 1, i - 2, i] }, true)).collect(),
+                6 => (2..n).map(|i| (vec![0, i - 1, i], true)).collect(),
+                mode => return Err(format!("unknown glTF primitive mode {mode}").into()),
+            };
+            for (face, closed) in faces {
+                if face.len() < 2 {
+                    continue;
+                }
+                polygons.push(if closed {
+                    Polygon::new(corners(&face), color, Transform::identity())
+                } else {
+                    Polygon::polyline(corners(&face), color, Transform::identity())
+                });
+            }
+        }
+        let mut mesh = Polyhedron::new(polygons);
+        for cloud in clouds {
+            mesh = mesh.with_point_cloud(cloud);
+        }
+        Ok(mesh)
+    }
+
+    fn base_color(&self, material: &Value) -> (u8, u8, u8) {
+        let Some(material) = material.as_u64().and_then(|m| self.document["materials"].get(m as usize)) else {
+            return DEFAULT_COLOR;
+        };
+        let c = numbers(&material["pbrMetallicRoughness"]["baseColorFactor"], &[1.0, 1.0, 1.0, 1.0]);
+        let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (channel(c[0]), channel(c[1]), channel(c[2]))
+    }
+
+    /// The elements of an accessor, each a list of its components.
+    fn accessor(&self, index: usize) -> AppResult<Vec<Vec<f64>>> {
+        let accessor = self.get("accessors", index)?;
+        let count = accessor["count"].as_u64().ok_or("glTF accessor has no count")? as usize;
+        let components = match accessor["type"].as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            other => return Err(format!("unknown glTF accessor type {other:?}").into()),
+        };
+        let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(format!("unknown glTF component type {other}").into()),
+        };
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+
+        if !accessor["sparse"].is_null() {
+            return Err("sparse glTF accessors are not supported".into());
+        }
+        let view = accessor["bufferView"].as_u64().ok_or("glTF accessor has no bufferView")?;
+        let view = self.get("bufferViews", view as usize)?;
+        let buffer = self.buffers.get(view["buffer"].as_u64().unwrap_or(0) as usize).ok_or("glTF buffer view refers to a missing buffer")?;
+        let offset = |value: &Value| value.as_u64().unwrap_or(0) as usize;
+        let element = size * components;
+        let stride = view["byteStride"].as_u64().map_or(element, |s| s as usize);
+        if stride < element {
+            return Err(format!("glTF byte stride {stride} is shorter than an element of {element} bytes").into());
+        }
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        // the last element has to end inside the buffer, which also keeps a made up count from allocating
+        let past_end = "glTF accessor reads past the end of its buffer";
+        let start = offset(&view["byteOffset"]).checked_add(offset(&accessor["byteOffset"])).ok_or(past_end)?;
+        let end = (count - 1).checked_mul(stride)
+            .and_then(|last| last.checked_add(start))
+            .and_then(|last| last.checked_add(element))
+            .ok_or(past_end)?;
+        if end > buffer.len() {
+            return Err(past_end.into());
+        }
+
+        Ok((0..count).map(|i| {
+            (0..components).map(|c| {
+                let at = start + (i * stride) + (c * size);
+                let b = &buffer[at..at + size];
+                match component_type {
+                    5120 => b[0] as i8 as f64 / if normalized { 127.0 } else { 1.0 },
+                    5121 => b[0] as f64 / if normalized { 255.0 } else { 1.0 },
+                    5122 => i16::from_le_bytes([b[0], b[1]]) as f64 / if normalized { 32767.0 } else { 1.0 },
+                    5123 => u16::from_le_bytes([b[0], b[1]]) as f64 / if normalized { 65535.0 } else { 1.0 },
+                    5125 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                }
+            }).collect()
+        }).collect())
+    }
+
+    fn get(&self, kind: &str, index: usize) -> AppResult<&'a Value> {
+        self.document[kind].get(index).ok_or_else(|| format!("glTF refers to missing {kind} {index}").into())
+    }
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or(&[])
+}
+
+fn indices(value: &Value) -> Vec<usize> {
+    array(value).iter().filter_map(Value::as_u64).map(|i| i as usize).collect()
+}
+
+fn numbers(value: &Value, default: &[f64]) -> Vec<f64> {
+    let numbers: Vec<f64> = array(value).iter().filter_map(Value::as_f64).collect();
+    if numbers.len() == default.len() { numbers } else { default.to_vec() }
+}
+
+/// Splits a column-major 4x4 matrix without shear into a transform and a scale.
+fn decompose(m: &[f64]) -> AppResult<(Transform, Point3d)> {
+    if m.len() != 16 {
+        return Err("glTF matrix must have 16 numbers".into());
+    }
+    let column = |c: usize| (m[4 * c], m[4 * c + 1], m[4 * c + 2]);
+    let length = |(x, y, z): Point3d| ((x * x) + (y * y) + (z * z)).sqrt();
+    let scale = (length(column(0)), length(column(1)), length(column(2)));
+    let (sx, sy, sz) = scale;
+    // rotation matrix entries, r(row, column)
+    let r = |row: usize, c: usize| {
+        let s = [sx, sy, sz][c];
+        if s == 0.0 { 0.0 } else { m[4 * c + row] / s }
+    };
+
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Quaternion::new(0.25 * s, (r(2, 1) - r(1, 2)) / s, (r(0, 2) - r(2, 0)) / s, (r(1, 0) - r(0, 1)) / s)
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        Quaternion::new((r(2, 1) - r(1, 2)) / s, 0.25 * s, (r(0, 1) + r(1, 0)) / s, (r(0, 2) + r(2, 0)) / s)
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        Quaternion::new((r(0, 2) - r(2, 0)) / s, (r(0, 1) + r(1, 0)) / s, 0.25 * s, (r(1, 2) + r(2, 1)) / s)
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        Quaternion::new((r(1, 0) - r(0, 1)) / s, (r(0, 2) + r(2, 0)) / s, (r(1, 2) + r(2, 1)) / s, 0.25 * s)
+    };
+    Ok((Transform::new((m[12], m[13], m[14]), q), scale))
+}
+
+fn base64(encoded: &str) -> AppResult<Vec<u8>> {
+    let value = |c: u8| -> AppResult<u32> {
+        Ok(match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(format!("invalid base64 character '{}'", c as char).into()),
+        } as u32)
+    };
+    let digits: Vec<u8> = encoded.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=').collect();
+    let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            n |= value(c)? << (18 - (6 * i));
+        }
+        bytes.extend(&n.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
@@ -1,39 +1,84 @@
-use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::app::{App, AppResult, CameraMode};
+use crate::keymap::Action;
+use crossterm::event::KeyEvent;
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // exit application on ESC
-        KeyCode::Esc => {
+    match app.keymap.action(key_event) {
+        Some(action) => perform(action, app),
+        None => Ok(()),
+    }
+}
+
+/// Does what a key bound to `action` does.
+pub fn perform(action: Action, app: &mut App) -> AppResult<()> {
+    let sensitivity = app.keymap.sensitivity;
+    let (step, turn) = (sensitivity.movement, sensitivity.turn);
+    let orbiting = app.camera_mode == CameraMode::Orbit;
+    match action {
+        Action::Quit => {
             app.running = false;
         }
-        KeyCode::Up => {
-            app.world.camera_pitch -= 0.05;
+        // a failed save is shown rather than ending the session, so the level can be saved elsewhere or fixed
+        Action::Save => {
+            app.message = Some(match app.save_level() {
+                Ok(path) => Ok(format!("saved {}", path.display())),
+                Err(e) => Err(format!("could not save {e}")),
+            });
+        }
+        Action::ExportSvg | Action::ExportPng => {
+            let exported = if action == Action::ExportSvg { app.export_svg() } else { app.export_png() };
+            app.message = Some(match exported {
+                Ok(path) => Ok(format!("exported {}", path.display())),
+                Err(e) => Err(format!("could not export {e}")),
+            });
+        }
+        Action::ToggleWireframe => {
+            app.wireframe = !app.wireframe;
+        }
+        Action::ToggleCamera => {
+            app.set_camera_mode(match app.camera_mode {
+                CameraMode::Fly => CameraMode::Orbit,
+                CameraMode::Orbit => CameraMode::Fly,
+            });
+        }
+        // pause and step animations, or change their speed
+        Action::TogglePause => app.playback.toggle_pause(),
+        Action::Step => app.playback.step(),
+        Action::Slower => app.playback.slower(),
+        Action::Faster => app.playback.faster(),
+        // an orbiting camera turns around its target, and moving forward and back brings it closer or farther away
+        Action::PitchUp if orbiting => app.orbit(-turn, 0.0, 1.0),
+        Action::PitchDown if orbiting => app.orbit(turn, 0.0, 1.0),
+        Action::YawLeft | Action::MoveLeft if orbiting => app.orbit(0.0, -turn, 1.0),
+        Action::YawRight | Action::MoveRight if orbiting => app.orbit(0.0, turn, 1.0),
+        Action::MoveForward if orbiting => app.orbit(0.0, 0.0, 1.0 - sensitivity.zoom),
+        Action::MoveBack if orbiting => app.orbit(0.0, 0.0, 1.0 + sensitivity.zoom),
+        Action::PitchUp => {
+            app.world.camera_pitch -= turn;
         }
-        KeyCode::Down => {
-            app.world.camera_pitch += 0.05;
+        Action::PitchDown => {
+            app.world.camera_pitch += turn;
         }
-        KeyCode::Left => {
-            app.world.camera_yaw -= 0.05;
+        Action::YawLeft => {
+            app.world.camera_yaw -= turn;
         }
-        KeyCode::Right => {
-            app.world.camera_yaw += 0.05;
+        Action::YawRight => {
+            app.world.camera_yaw += turn;
         }
 
-        KeyCode::Char('w') => {
-            app.world.world_translation_z -= 0.05;
+        Action::MoveForward => {
+            app.world.world_translation_z -= step;
         }
-        KeyCode::Char('a') => {
-            app.world.world_translation_x += 0.05;
+        Action::MoveLeft => {
+            app.world.world_translation_x += step;
         }
-        KeyCode::Char('s') => {
-            app.world.world_translation_z += 0.05;
+        Action::MoveBack => {
+            app.world.world_translation_z += step;
         }
-        KeyCode::Char('d') => {
-            app.world.world_translation_x -= 0.05;
+        Action::MoveRight => {
+            app.world.world_translation_x -= step;
         }
-        _ => {}
     }
     Ok(())
 }
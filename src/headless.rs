@@ -0,0 +1,27 @@
+use tui::backend::TestBackend;
+use tui::buffer::Buffer;
+use tui::Terminal;
+use crate::app::{App, AppResult};
+
+/// Renders a frame of `app` at `width` by `height` cells without a terminal.
+///
+/// The buffer holds the symbol and colours of every cell.
+pub fn render(app: &mut App, width: u16, height: u16) -> AppResult<Buffer> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| app.render(frame))?;
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// Renders a frame of `app` as lines of text, one per row of cells.
+pub fn render_lines(app: &mut App, width: u16, height: u16) -> AppResult<Vec<String>> {
+    Ok(lines(&render(app, width, height)?))
+}
+
+/// The symbols of a buffer, one line per row with trailing blanks removed.
+pub fn lines(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+    (area.top()..area.bottom()).map(|y| {
+        let line: String = (area.left()..area.right()).map(|x| buffer.get(x, y).symbol.as_str()).collect();
+        line.trim_end().to_string()
+    }).collect()
+}
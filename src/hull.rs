@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use tui::style::Color;
+use crate::scene::{add, cross, dot, length, normalize, scale, sub, Point3d, Polyhedron};
+
+/// A convex polyhedron wrapping every point of `points`.
+pub fn convex_polyhedron(points: &[Point3d], color: Color) -> Polyhedron {
+    let faces: Vec<Vec<usize>> = convex_hull(points).into_iter().filter(|f| f.len() >= 3).collect();
+    Polyhedron::from_mesh(points, &faces, color)
+}
+
+/// The faces of the convex hull of `points`, as indices into `points`.
+///
+/// Every face is planar and wound counter-clockwise seen from outside.
+/// Points lying on a face or an edge are left out. A flat point set gives a single face,
+/// and one with fewer than three non-collinear points gives none.
+pub fn convex_hull(points: &[Point3d]) -> Vec<Vec<usize>> {
+    let extent = points.iter()
+        .map(|&p| length(sub(p, points[0])))
+        .fold(0.0, f64::max);
+    // coinciding points have no direction between them to start from
+    if extent == 0.0 {
+        return Vec::new();
+    }
+    let eps = extent * 1e-9;
+
+    // initial tetrahedron: two far apart points, the point farthest from their line and the one farthest from their plane
+    let Some(&a) = points.first() else { return Vec::new() };
+    let Some(b) = farthest(points, |p| length(sub(p, a))) else { return Vec::new() };
+    let ab = normalize(sub(points[b], a));
+    let Some(c) = farthest(points, |p| length(cross(sub(p, a), ab))) else { return Vec::new() };
+    if length(cross(sub(points[c], a), ab)) <= eps {
+        return Vec::new(); // all on one line
+    }
+    let normal = normalize(cross(sub(points[b], a), sub(points[c], a)));
+    let Some(d) = farthest(points, |p| dot(sub(p, a), normal).abs()) else { return Vec::new() };
+    if dot(sub(points[d], a), normal).abs() <= eps {
+        let all: Vec<usize> = (0..points.len()).collect();
+        return vec![planar_hull(points, &all, normal, eps)];
+    }
+
+    let inside = scale(add(add(a, points[b]), add(points[c], points[d])), 0.25);
+    let mut faces: Vec<Triangle> = [[0, b, c], [0, b, d], [0, c, d], [b, c, d]].iter()
+        .map(|&[i, j, k]| Triangle::outward(points, [i, j, k], inside))
+        .collect();
+
+    for p in 0..points.len() {
+        let visible: Vec<bool> = faces.iter().map(|f| f.distance(points, points[p]) > eps).collect();
+        if !visible.contains(&true) {
+            continue;
+        }
+
+        // the edges between visible and hidden faces outline the hole the new point will close
+        let visible_edges: HashSet<(usize, usize)> = faces.iter()
+            .zip(&visible)
+            .filter(|(_, &v)| v)
+            .flat_map(|(f, _)| f.edges())
+            .collect();
+        // walking the faces in order, rather than the set, keeps the hull the same from run to run
+        let horizon: Vec<(usize, usize)> = faces.iter()
+            .zip(&visible)
+            .filter(|(_, &v)| v)
+            .flat_map(|(f, _)| f.edges())
+            .filter(|&(i, j)| !visible_edges.contains(&(j, i)))
+            .collect();
+
+        faces = faces.into_iter()
+            .zip(visible)
+            .filter(|(_, v)| !v)
+            .map(|(f, _)| f)
+            .collect();
+        for (i, j) in horizon {
+            faces.push(Triangle::outward(points, [i, j, p], inside));
+        }
+    }
+
+    // merge the triangles sharing a plane into one face
+    let mut faces_by_plane: Vec<(Point3d, f64, Vec<usize>)> = Vec::new();
+    for f in faces {
+        let normal = f.normal(points);
+        let offset = dot(normal, points[f.vertices[0]]);
+        let same_plane = faces_by_plane.iter_mut().find(|(n, o, _)| {
+            length(sub(*n, normal)) < 1e-6 && (o - offset).abs() <= eps.max(1e-9)
+        });
+        match same_plane {
+            Some((_, _, vertices)) => vertices.extend(f.vertices),
+            None => faces_by_plane.push((normal, offset, f.vertices.to_vec())),
+        }
+    }
+
+    faces_by_plane.into_iter().map(|(normal, _, mut vertices)| {
+        vertices.sort_unstable();
+        vertices.dedup();
+        planar_hull(points, &vertices, normal, eps)
+    }).collect()
+}
+
+struct Triangle {
+    vertices: [usize; 3],
+}
+
+impl Triangle {
+    /// A triangle wound so that its normal points away from `inside`.
+    fn outward(points: &[Point3d], vertices: [usize; 3], inside: Point3d) -> Self {
+        let mut t = Triangle { vertices };
+        if t.distance(points, inside) > 0.0 {
+            t.vertices.swap(1, 2);
+        }
+        t
+    }
+
+    fn normal(&self, points: &[Point3d]) -> Point3d {
+        let [a, b, c] = self.vertices.map(|i| points[i]);
+        normalize(cross(sub(b, a), sub(c, a)))
+    }
+
+    /// Signed distance from the triangle's plane, positive on the outer side.
+    fn distance(&self, points: &[Point3d], p: Point3d) -> f64 {
+        dot(self.normal(points), sub(p, points[self.vertices[0]]))
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+fn farthest(points: &[Point3d], distance: impl Fn(Point3d) -> f64) -> Option<usize> {
+    (0..points.len()).max_by(|&i, &j| distance(points[i]).total_cmp(&distance(points[j])))
+}
+
+/// The corners of the convex outline of coplanar points, counter-clockwise around `normal`.
+fn planar_hull(points: &[Point3d], indices: &[usize], normal: Point3d, eps: f64) -> Vec<usize> {
+    let any = if normal.0.abs() < 0.9 { (1.0, 0.0, 0.0) } else { (0.0, 1.0, 0.0) };
+    let u = normalize(cross(normal, any));
+    let v = cross(normal, u);
+    let flat = |i: usize| (dot(points[i], u), dot(points[i], v));
+
+    let mut sorted = indices.to_vec();
+    sorted.sort_by(|&i, &j| {
+        let (a, b) = (flat(i), flat(j));
+        a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+    });
+    let turn = |o: usize, a: usize, b: usize| {
+        let (o, a, b) = (flat(o), flat(a), flat(b));
+        ((a.0 - o.0) * (b.1 - o.1)) - ((a.1 - o.1) * (b.0 - o.0))
+    };
+
+    // Andrew's monotone chain
+    let mut hull: Vec<usize> = Vec::new();
+    for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let start = hull.len();
+        for i in pass {
+            while hull.len() >= start + 2 && turn(hull[hull.len() - 2], hull[hull.len() - 1], i) <= eps * eps {
+                hull.pop();
+            }
+            hull.push(i);
+        }
+        hull.pop();
+    }
+    hull
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Save,
+    ExportSvg,
+    ExportPng,
+    ToggleWireframe,
+    ToggleCamera,
+    TogglePause,
+    Step,
+    Slower,
+    Faster,
+    /// Moves the camera forward, or orbits closer.
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    PitchUp,
+    PitchDown,
+    YawLeft,
+    YawRight,
+}
+
+impl fmt::Display for Action {
+    /// The name of the action in the config file, such as `move_forward`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in format!("{self:?}").chars().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                f.write_str("_")?;
+            }
+            write!(f, "{}", c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// A key with the modifiers held down, as written in the config file, such as `w`, `ctrl+s` or `shift+up`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // a character already tells whether shift was held, and terminals differ in also reporting it
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => KeyBinding {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => KeyBinding { code, modifiers },
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        KeyBinding::new(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("invalid key '{text}': {reason}");
+        // the key comes last, so that "ctrl++" is the plus key
+        let (modifiers, key) = match text.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest.trim_end_matches('+'), "+"),
+            _ => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut held = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            held |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid(&format!("unknown modifier '{modifier}', expected ctrl, alt or shift"))),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(invalid("unknown key")),
+                },
+            },
+        };
+        Ok(KeyBinding::new(code, held))
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl+"), (KeyModifiers::ALT, "alt+"), (KeyModifiers::SHIFT, "shift+")] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{}", format!("{code:?}").to_lowercase()),
+        }
+    }
+}
+
+/// How far a key press moves or turns the camera.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sensitivity {
+    /// Distance the camera moves per press.
+    #[serde(rename = "move")]
+    pub movement: f64,
+    /// Angle the camera turns per press, in radians.
+    pub turn: f64,
+    /// Fraction of the distance an orbiting camera moves closer or farther per press.
+    pub zoom: f64,
+}
+
+impl Default for Sensitivity {
+    fn default() -> Self {
+        Sensitivity { movement: 0.05, turn: 0.05, zoom: 0.05 }
+    }
+}
+
+/// Which action each key does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    actions: HashMap<KeyBinding, Action>,
+    pub sensitivity: Sensitivity,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let key = |code| KeyBinding::new(code, KeyModifiers::NONE);
+        let char = |c| key(KeyCode::Char(c));
+        let bindings = [
+            (key(KeyCode::Esc), Action::Quit),
+            (KeyBinding::new(KeyCode::Char('s'), KeyModifiers::CONTROL), Action::Save),
+            (char('p'), Action::ExportSvg),
+            (char('i'), Action::ExportPng),
+            (key(KeyCode::Tab), Action::ToggleWireframe),
+            (char('c'), Action::ToggleCamera),
+            (char(' '), Action::TogglePause),
+            (char('.'), Action::Step),
+            (char('['), Action::Slower),
+            (char(']'), Action::Faster),
+            (char('w'), Action::MoveForward),
+            (char('s'), Action::MoveBack),
+            (char('a'), Action::MoveLeft),
+            (char('d'), Action::MoveRight),
+            (key(KeyCode::Up), Action::PitchUp),
+            (key(KeyCode::Down), Action::PitchDown),
+            (key(KeyCode::Left), Action::YawLeft),
+            (key(KeyCode::Right), Action::YawRight),
+        ];
+        Keymap { actions: bindings.into_iter().collect(), sensitivity: Sensitivity::default() }
+    }
+}
+
+impl Keymap {
+    /// The default keys, with the keys of the actions in `bindings` replaced.
+    ///
+    /// A key may do only one action, so a default key bound to another action here stops doing its default one.
+    pub fn new(bindings: &HashMap<Action, Vec<KeyBinding>>, sensitivity: Sensitivity) -> Result<Self, String> {
+        let mut keymap = Keymap { sensitivity, ..Keymap::default() };
+        keymap.actions.retain(|key, action| !bindings.contains_key(action) && !bindings.values().flatten().any(|k| k == key));
+        for (action, keys) in bindings {
+            for key in keys {
+                match keymap.actions.insert(*key, *action) {
+                    Some(other) if other != *action => {
+                        return Err(format!("key '{key}' is bound to both {other} and {action}"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// The action of a key press, if any.
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        self.actions.get(&KeyBinding::from(event)).copied()
+    }
+
+    /// The keys bound to `action`.
+    pub fn keys(&self, action: Action) -> Vec<KeyBinding> {
+        self.actions.iter().filter(|(_, a)| **a == action).map(|(key, _)| *key).collect()
+    }
+}
@@ -0,0 +1,474 @@
+use std::f64::consts::PI;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::ser::{CompactFormatter, Formatter};
+use crate::app::{AppResult, WorldMetrics};
+use crate::color::{parse_color, to_rgb};
+use crate::hull::convex_polyhedron;
+use crate::plot::{function_surface, Expression};
+use crate::pointcloud::PointCloud;
+use crate::ply::Ply;
+use crate::primitives;
+use crate::quaternions::Quaternion;
+use crate::scene::{Animation, Polygon, Polyhedron, Transform};
+use crate::surface::{Shading, Surface};
+use crate::gltf;
+
+/// A world described in a JSON level file.
+///
+/// ```json
+/// {
+///   "camera": { "position": [0, 0, -2] },
+///   "objects": [
+///     { "name": "box", "mesh": { "type": "cube", "size": 1 }, "color": "#3080ff",
+///       "translation": [0, 0, 7], "animation": { "type": "spin", "axis": [0, 1, 0], "degrees_per_second": 45 } }
+///   ]
+/// }
+/// ```
+///
+/// Paths to mesh files are relative to the level file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Level {
+    pub camera: Camera,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lights: Vec<Light>,
+    pub objects: Vec<Object>,
+}
+
+/// Where the camera starts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Camera {
+    pub position: [f64; 3],
+    /// Radians.
+    pub pitch: f64,
+    /// Radians.
+    pub yaw: f64,
+}
+
+/// A light source, kept with the level for shading; the line renderer draws without lights.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Light {
+    pub position: [f64; 3],
+    #[serde(default = "white")]
+    pub color: String,
+    #[serde(default = "one")]
+    pub intensity: f64,
+}
+
+/// A scene graph node: a mesh placed relative to its parent, and its children.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Object {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default = "group")]
+    pub mesh: Mesh,
+    #[serde(default = "white")]
+    pub color: String,
+    #[serde(default)]
+    pub translation: [f64; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<Motion>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Object>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rotation {
+    pub axis: [f64; 3],
+    pub degrees: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Motion {
+    Spin { axis: [f64; 3], degrees_per_second: f64 },
+    Oscillate { offset: [f64; 3], period: f64 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coloring {
+    /// The object's colour.
+    #[default]
+    Solid,
+    /// Coloured by height.
+    Height,
+}
+
+/// What an object looks like; see [`primitives`] for the generated shapes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Mesh {
+    /// Nothing of its own, only children.
+    Group,
+    Tetrahedron { size: f64 },
+    Cube { size: f64 },
+    Octahedron { size: f64 },
+    Dodecahedron { size: f64 },
+    Icosahedron { size: f64 },
+    UvSphere { radius: f64, segments: usize, rings: usize },
+    IcoSphere { radius: f64, subdivisions: usize },
+    Torus { major_radius: f64, minor_radius: f64, segments: usize, sides: usize },
+    Cylinder { radius: f64, height: f64, segments: usize },
+    Cone { radius: f64, height: f64, segments: usize },
+    Plane { size: f64, segments: usize },
+    /// A single flat face.
+    Polygon { vertices: Vec<[f64; 3]> },
+    /// The convex hull of a point set.
+    Hull { points: Vec<[f64; 3]> },
+    /// The graph of z = `expression` of x and y.
+    Function { expression: String, x: [f64; 2], y: [f64; 2], resolution: usize, #[serde(default)] coloring: Coloring },
+    /// A CSV or PGM heightmap.
+    Terrain { path: String, cell_size: f64, height_scale: f64, #[serde(default)] coloring: Coloring },
+    /// A PLY or glTF file.
+    Model { path: String },
+    /// A CSV, XYZ or PLY point set.
+    PointCloud { path: String },
+}
+
+fn white() -> String {
+    "white".to_string()
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn group() -> Mesh {
+    Mesh::Group
+}
+
+impl Level {
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> AppResult<()> {
+        let mut json = Vec::new();
+        self.serialize(&mut serde_json::Serializer::with_formatter(&mut json, LevelFormatter::default()))?;
+        json.push(b'\n');
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Builds the scene graph, reading mesh files relative to `directory`.
+    pub fn build(&self, directory: &Path) -> AppResult<Polyhedron> {
+        let children = self.objects.iter().map(|o| o.build(directory)).collect::<AppResult<_>>()?;
+        Ok(Polyhedron::group(children))
+    }
+
+    /// The mesh files the level reads, relative to `directory`.
+    pub fn files(&self, directory: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for object in &self.objects {
+            object.files(directory, &mut files);
+        }
+        files
+    }
+
+    /// Places the camera of `world` at the level's start.
+    pub fn apply_camera(&self, world: &mut WorldMetrics) {
+        let [x, y, z] = self.camera.position;
+        world.world_translation_x = -x;
+        world.world_translation_y = -y;
+        world.world_translation_z = -z;
+        world.camera_pitch = self.camera.pitch;
+        world.camera_yaw = self.camera.yaw;
+    }
+
+    /// Moves the level's camera start to where the camera of `world` is.
+    pub fn store_camera(&mut self, world: &WorldMetrics) {
+        self.camera = Camera {
+            position: [-world.world_translation_x, -world.world_translation_y, -world.world_translation_z].map(|v| v + 0.0), // no -0.0
+            pitch: world.camera_pitch,
+            yaw: world.camera_yaw,
+        };
+    }
+
+    /// A level showing a single model (`.ply`, `.gltf`) or point cloud (`.csv`, `.xyz`, `.txt`).
+    pub fn model(path: &Path) -> AppResult<Self> {
+        let file = path.to_string_lossy().into_owned();
+        let mesh = match path.extension().and_then(|e| e.to_str()) {
+            Some("ply" | "gltf") => Mesh::Model { path: file },
+            Some("csv" | "xyz" | "txt") => Mesh::PointCloud { path: file },
+            _ => return Err(format!("{}: expected a .json level, or a .ply, .gltf, .csv, .xyz or .txt file", path.display()).into()),
+        };
+        Ok(Level {
+            objects: vec![Object {
+                name: path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default(),
+                mesh,
+                color: white(),
+                translation: [0.0; 3],
+                rotation: None,
+                animation: None,
+                children: Vec::new(),
+            }],
+            ..Level::default()
+        })
+    }
+
+    /// The scene shown when no level is loaded: an eye, a pentagram and a dodecahedron.
+    pub fn demo() -> Self {
+        let pentagram: Vec<[f64; 3]> = (0..5).map(|n| {
+            let step = 2.0*PI/5.0;
+            let offset = -PI/10.0;
+            let angle = n as f64 *2.0* step + offset;
+            [angle.cos(), angle.sin(), 0.0]
+        }).collect();
+
+        let phi = (1.0 + 5.0f64.sqrt())/2.0;
+        let phi_i = 1.0 / phi;
+        let mut dodecavertexes: Vec<[f64; 3]> = Vec::new();
+        for a in [-1.0, 1.0] {
+            for b in [-1.0, 1.0] {
+                for c in [-1.0, 1.0] {
+                    dodecavertexes.push([a, b, c]);
+                }
+                dodecavertexes.push([0.0, a * phi_i, b * phi]);
+                dodecavertexes.push([a * phi_i, b * phi, 0.0]);
+                dodecavertexes.push([a * phi, 0.0, b * phi_i]);
+            }
+        }
+
+        let (a, b, c) = (1.0, 0.5, 0.8);
+        let eye = vec![[-a, 0.0, 0.0], [-c, b, 0.0], [c, b, 0.0], [a, 0.0, 0.0], [c, -b, 0.0], [-c, -b, 0.0]];
+
+        let object = |name: &str, mesh: Mesh, color: &str, translation: [f64; 3]| Object {
+            name: name.to_string(),
+            mesh,
+            color: color.to_string(),
+            translation,
+            rotation: None,
+            animation: None,
+            children: Vec::new(),
+        };
+        Level {
+            camera: Camera::default(),
+            lights: Vec::new(),
+            objects: vec![
+                object("eye", Mesh::Polygon { vertices: eye }, "red", [-1.0, -1.0, 8.0]),
+                object("pentagram", Mesh::Polygon { vertices: pentagram }, "red", [0.0, 0.0, 7.0]),
+                object("dodecahedron", Mesh::Hull { points: dodecavertexes }, "green", [3.5, 0.5, 10.0]),
+            ],
+        }
+    }
+}
+
+impl Object {
+    pub fn build(&self, directory: &Path) -> AppResult<Polyhedron> {
+        let color = parse_color(&self.color)?;
+        let shading = |coloring: &Coloring| match coloring {
+            Coloring::Solid => Shading::Wireframe(color),
+            Coloring::Height => Shading::Height,
+        };
+        let point = |[x, y, z]: [f64; 3]| (x, y, z);
+        let file = |path: &str| directory.join(path);
+
+        let mesh = match &self.mesh {
+            Mesh::Group => Polyhedron::group(Vec::new()),
+            Mesh::Tetrahedron { size } => primitives::tetrahedron(*size, color),
+            Mesh::Cube { size } => primitives::cube(*size, color),
+            Mesh::Octahedron { size } => primitives::octahedron(*size, color),
+            Mesh::Dodecahedron { size } => primitives::dodecahedron(*size, color),
+            Mesh::Icosahedron { size } => primitives::icosahedron(*size, color),
+            Mesh::UvSphere { radius, segments, rings } => primitives::uv_sphere(*radius, *segments, *rings, color),
+            Mesh::IcoSphere { radius, subdivisions } => primitives::ico_sphere(*radius, (*subdivisions).min(5), color),
+            Mesh::Torus { major_radius, minor_radius, segments, sides } => primitives::torus(*major_radius, *minor_radius, *segments, *sides, color),
+            Mesh::Cylinder { radius, height, segments } => primitives::cylinder(*radius, *height, *segments, color),
+            Mesh::Cone { radius, height, segments } => primitives::cone(*radius, *height, *segments, color),
+            Mesh::Plane { size, segments } => primitives::plane(*size, *segments, color),
+            Mesh::Polygon { vertices } => {
+                if vertices.len() < 2 {
+                    return Err(format!("polygon '{}' needs at least two vertices", self.name).into());
+                }
+                Polyhedron::new(vec![Polygon::new(vertices.iter().cloned().map(point).collect(), color, Transform::identity())])
+            }
+            Mesh::Hull { points } => {
+                let points: Vec<_> = points.iter().cloned().map(point).collect();
+                convex_polyhedron(&points, color)
+            }
+            Mesh::Function { expression, x, y, resolution, coloring } => {
+                let f = Expression::parse(expression, &["x", "y"])?;
+                function_surface(|x, y| f.eval(&[x, y]), (x[0], x[1]), (y[0], y[1]), *resolution).to_polyhedron(shading(coloring))
+            }
+            Mesh::Terrain { path, cell_size, height_scale, coloring } => {
+                Surface::load(file(path), *cell_size, *height_scale)?.to_polyhedron(shading(coloring))
+            }
+            Mesh::Model { path } => load_model(&file(path), color)?,
+            Mesh::PointCloud { path } => Polyhedron::group(Vec::new()).with_point_cloud(PointCloud::load(file(path), to_rgb(color))?),
+        };
+
+        let rotation = match &self.rotation {
+            Some(Rotation { axis, degrees }) => Quaternion::from(point(*axis)).rotatation(degrees.to_radians()),
+            None => Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        };
+        let mut node = mesh.with_transform(Transform::new(point(self.translation), rotation));
+        if let Some(motion) = &self.animation {
+            node = node.with_animation(match motion {
+                Motion::Spin { axis, degrees_per_second } => Animation::Spin { axis: point(*axis), speed: degrees_per_second.to_radians() },
+                Motion::Oscillate { offset, period } => Animation::Oscillate { offset: point(*offset), period: *period },
+            });
+        }
+        for child in &self.children {
+            node = node.with_child(child.build(directory)?);
+        }
+        Ok(node)
+    }
+
+    fn files(&self, directory: &Path, files: &mut Vec<PathBuf>) {
+        match &self.mesh {
+            Mesh::Terrain { path, .. } | Mesh::Model { path } | Mesh::PointCloud { path } => files.push(directory.join(path)),
+            _ => {}
+        }
+        for child in &self.children {
+            child.files(directory, files);
+        }
+    }
+}
+
+/// Reads a PLY (`.ply`) or glTF (`.gltf`) model; PLY faces without vertex colours get `color`.
+pub fn load_model(path: &Path, color: tui::style::Color) -> AppResult<Polyhedron> {
+    let result = match path.extension().and_then(|e| e.to_str()) {
+        Some("ply") => Ply::load(path).and_then(|ply| ply.to_polyhedron(to_rgb(color))),
+        Some("gltf") => gltf::load(path),
+        _ => Err("unsupported model format, expected .ply or .gltf".into()),
+    };
+    result.map_err(|e| format!("{}: {e}", path.display()).into())
+}
+
+/// Pretty prints JSON like [`serde_json::ser::PrettyFormatter`], but puts lists of numbers such as
+/// `[1.0, 0.0, 7.0]` on one line, keeping vertex lists readable.
+#[derive(Default)]
+struct LevelFormatter {
+    indent: usize,
+    /// Whether the innermost object has a member yet.
+    has_value: bool,
+    /// How each open list is laid out.
+    lists: Vec<ListLayout>,
+    /// Whether the next value is the first item of the innermost list, when it starts a list item.
+    item: Option<bool>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListLayout {
+    /// No items yet.
+    Empty,
+    /// Numbers only so far, on one line.
+    Numbers,
+    /// One item per line.
+    Lines,
+}
+
+impl LevelFormatter {
+    /// Writes what goes before a value that is a list item, which depends on whether it is a number.
+    fn start_value<W: ?Sized + Write>(&mut self, writer: &mut W, number: bool) -> io::Result<()> {
+        let (Some(first), Some(layout)) = (self.item.take(), self.lists.last_mut()) else { return Ok(()) };
+        if number && *layout != ListLayout::Lines {
+            *layout = ListLayout::Numbers;
+            return writer.write_all(if first { b"" } else { b", " });
+        }
+        *layout = ListLayout::Lines;
+        writer.write_all(if first { b"\n" } else { b",\n" })?;
+        self.write_indent(writer)
+    }
+
+    fn write_indent<W: ?Sized + Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&b"  ".repeat(self.indent))
+    }
+}
+
+/// Formatter methods writing a number, which start the value first.
+macro_rules! write_numbers {
+    ($($method:ident: $type:ty),*) => {$(
+        fn $method<W: ?Sized + Write>(&mut self, writer: &mut W, value: $type) -> io::Result<()> {
+            self.start_value(writer, true)?;
+            CompactFormatter.$method(writer, value)
+        }
+    )*};
+}
+
+impl Formatter for LevelFormatter {
+    write_numbers!(
+        write_i8: i8, write_i16: i16, write_i32: i32, write_i64: i64, write_i128: i128,
+        write_u8: u8, write_u16: u16, write_u32: u32, write_u64: u64, write_u128: u128,
+        write_f32: f32, write_f64: f64, write_number_str: &str
+    );
+
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer, false)?;
+        writer.write_all(b"null")
+    }
+
+    fn write_bool<W: ?Sized + Write>(&mut self, writer: &mut W, value: bool) -> io::Result<()> {
+        self.start_value(writer, false)?;
+        CompactFormatter.write_bool(writer, value)
+    }
+
+    fn begin_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer, false)?;
+        writer.write_all(b"\"")
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer, false)?;
+        self.indent += 1;
+        self.lists.push(ListLayout::Empty);
+        writer.write_all(b"[")
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.indent -= 1;
+        if self.lists.pop() == Some(ListLayout::Lines) {
+            writer.write_all(b"\n")?;
+            self.write_indent(writer)?;
+        }
+        writer.write_all(b"]")
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, _: &mut W, first: bool) -> io::Result<()> {
+        self.item = Some(first);
+        Ok(())
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer, false)?;
+        self.indent += 1;
+        self.has_value = false;
+        writer.write_all(b"{")
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.indent -= 1;
+        if self.has_value {
+            writer.write_all(b"\n")?;
+            self.write_indent(writer)?;
+        }
+        writer.write_all(b"}")
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        writer.write_all(if first { b"\n" } else { b",\n" })?;
+        self.write_indent(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _: &mut W) -> io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+}
@@ -11,3 +11,63 @@ pub mod tui;
 pub mod handler;
 
 pub mod quaternions;
+
+/// Scene graph and projection.
+pub mod scene;
+
+/// Procedurally generated meshes.
+pub mod primitives;
+
+/// Convex hulls of point sets.
+pub mod hull;
+
+/// Point grids and terrain.
+pub mod surface;
+
+/// Function and parametric plots.
+pub mod plot;
+
+/// Point clouds drawn as single dots.
+pub mod pointcloud;
+
+/// PLY file reader.
+pub mod ply;
+
+/// glTF 2.0 import.
+pub mod gltf;
+
+/// Terminal colour names and values.
+pub mod color;
+
+/// Level files.
+pub mod level;
+
+/// Drawing modes for the terminal.
+pub mod render;
+
+/// Rendering without a terminal.
+pub mod headless;
+
+/// SVG export.
+pub mod svg;
+
+/// PNG and PPM export.
+pub mod raster;
+
+/// Time sources for animation.
+pub mod clock;
+
+/// The shape of terminal cells, from the size of the terminal in pixels.
+pub mod cell;
+
+/// Recording sessions as asciinema casts.
+pub mod cast;
+
+/// Actions the keys are bound to.
+pub mod keymap;
+
+/// The user's config file.
+pub mod config;
+
+/// Command line arguments.
+pub mod cli;
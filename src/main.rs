@@ -1,42 +1,135 @@
 
 
-use std::io;
+use std::io::{self, Stderr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use clap::Parser;
+#[cfg(unix)]
+use signal_hook::consts::{SIGINT, SIGTERM};
 use tui::backend::CrosstermBackend;
-use tui::Terminal;
+use tui::layout::Rect;
+use tui::{Terminal, TerminalOptions, Viewport};
 use dddragon::app::{App, AppResult};
-use dddragon::event::{Event, EventHandler};
+use dddragon::cell;
+use dddragon::cli::Args;
+use dddragon::clock::FixedStep;
+use dddragon::event::{self, Event, EventHandler, TerminalSetup};
 use dddragon::handler::handle_key_events;
+use dddragon::headless;
+use dddragon::raster;
+use dddragon::svg;
 use dddragon::tui::Tui;
 
 fn main() -> AppResult<()> {
     // Create an application.
+    let args = Args::parse();
     let mut app = App::new();
+    let config = args.config()?;
+    args.apply(&mut app, &config)?;
+    let tick_rate = config.display.tick_rate;
+
+    if args.headless || args.svg.is_some() || args.image.is_some() {
+        app.clock = Box::new(FixedStep::new(args.time * 1000.0, 0.0));
+        let (width, height) = args.size;
+        let lines = headless::render_lines(&mut app, width, height)?;
+        let options = app.image.clone();
+        if let Some(path) = &args.svg {
+            svg::export(&mut app, path, &options)?;
+        }
+        if let Some(path) = &args.image {
+            raster::export(&mut app, path, &options)?;
+        }
+        if args.headless {
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        return Ok(());
+    }
+
+    // Measure the terminal's cells, before the event handler starts reading its input;
+    // a replay uses the cells it was recorded with instead.
+    let replay = args.replay.as_ref().map(event::load).transpose()?;
+    match &replay {
+        Some((setup, _)) => app.character_ratio = setup.character_ratio,
+        None if args.character_ratio.is_none() => {
+            if let Some(ratio) = cell::character_ratio() {
+                app.character_ratio = ratio;
+            }
+        }
+        None => {}
+    }
+
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
-    let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(20);
-    let s = terminal.size();
-    println!("{s:?}");
+    let (terminal, setup) = match &replay {
+        // a replay is drawn at the size it was recorded at, whatever the size of this terminal
+        Some((setup, _)) => {
+            let (width, height) = setup.size;
+            let viewport = Viewport::fixed(Rect::new(0, 0, width, height));
+            (Terminal::with_options(backend, TerminalOptions { viewport })?, *setup)
+        }
+        None => {
+            let terminal = Terminal::new(backend)?;
+            let size = terminal.size()?;
+            let setup = TerminalSetup { size: (size.width, size.height), character_ratio: app.character_ratio };
+            (terminal, setup)
+        }
+    };
+    let mut events = match replay {
+        Some((_, recorded)) => EventHandler::replay(recorded, tick_rate),
+        None => EventHandler::new(tick_rate),
+    };
+    if let Some(path) = &args.record_input {
+        events.record(path, setup)?;
+    }
+    if args.replay.is_some() || args.record_input.is_some() {
+        // a frame every tick, so that a replay animates exactly like its recording
+        app.clock = Box::new(FixedStep::new(0.0, tick_rate as f64));
+    }
     let mut tui = Tui::new(terminal, events);
+    if let Some(path) = &args.record {
+        tui.record(path)?;
+    }
     tui.init()?;
 
 
 
+    // SIGINT and SIGTERM end the main loop, so that the terminal is restored as on quitting.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [SIGINT, SIGTERM] {
+        // a second signal ends the program at once, in case the first goes unnoticed
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&interrupted))?;
+        signal_hook::flag::register(signal, Arc::clone(&interrupted))?;
+    }
+
     // Start the main loop.
-    while app.running {
-        // Render the user interface.
-        
+    let result = run(&mut app, &mut tui, &interrupted);
+
+    // Exit the user interface, even when the main loop failed.
+    tui.exit()?;
+    result
+}
+
+/// Handles events until the app quits or a signal interrupts it.
+fn run(app: &mut App, tui: &mut Tui<CrosstermBackend<Stderr>>, interrupted: &AtomicBool) -> AppResult<()> {
+    while app.running && !interrupted.load(Ordering::Relaxed) {
         // Handle events.
         match tui.events.next()? {
-            Event::Tick => {app.tick(); tui.draw(&mut app)?},
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
+            Event::Tick => {app.tick(); tui.draw(app)?},
+            Event::Key(key_event) => handle_key_events(key_event, app)?,
             Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+            // redraw at once, rather than stretched until the next tick
+            Event::Resize(width, height) => {
+                app.resize(width, height);
+                tui.resize(width, height)?;
+                tui.draw(app)?;
+            }
+            Event::Error(error) => return Err(error.into()),
+            Event::Custom(message) => app.message = Some(Ok(message)),
             Event::Default => {}
         }
     }
-
-    // Exit the user interface.
-    tui.exit()?;
     Ok(())
 }
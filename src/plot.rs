@@ -0,0 +1,296 @@
+use tui::style::Color;
+use crate::app::AppResult;
+use crate::scene::{Point3d, Polygon, Polyhedron, Transform};
+use crate::surface::Surface;
+
+/// Interval of a plot variable, from start to end.
+pub type Domain = (f64, f64);
+
+/// The graph of z = f(x, y) sampled at `resolution` steps along each axis.
+///
+/// Plots use the mathematical convention of z pointing up, which becomes the
+/// renderer's y axis. Samples that are not finite, like `sqrt(-1)`, leave holes.
+pub fn function_surface(f: impl Fn(f64, f64) -> f64, x: Domain, y: Domain, resolution: usize) -> Surface {
+    parametric_surface(|u, v| (u, v, f(u, v)), x, y, resolution)
+}
+
+/// The surface traced by (x(u, v), y(u, v), z(u, v)) sampled at `resolution` steps along each parameter.
+pub fn parametric_surface(f: impl Fn(f64, f64) -> Point3d, u: Domain, v: Domain, resolution: usize) -> Surface {
+    let resolution = resolution.max(1);
+    let map = steps(v, resolution).map(|v| {
+        steps(u, resolution).map(|u| finite(z_up(f(u, v)))).collect()
+    }).collect();
+    Surface::new(map)
+}
+
+/// The curve traced by (x(t), y(t), z(t)) sampled at `resolution` steps.
+pub fn parametric_curve(f: impl Fn(f64) -> Point3d, t: Domain, resolution: usize, color: Color) -> Polyhedron {
+    let mut polylines: Vec<Polygon> = Vec::new();
+    let mut current: Vec<Point3d> = Vec::new();
+    for point in steps(t, resolution.max(1)).map(|t| finite(z_up(f(t)))) {
+        match point {
+            Some(p) => current.push(p),
+            None => polylines.extend(polyline(std::mem::take(&mut current), color)),
+        }
+    }
+    polylines.extend(polyline(current, color));
+    Polyhedron::new(polylines)
+}
+
+fn polyline(points: Vec<Point3d>, color: Color) -> Option<Polygon> {
+    (points.len() > 1).then(|| Polygon::polyline(points, color, Transform::identity()))
+}
+
+fn steps((start, end): Domain, resolution: usize) -> impl Iterator<Item = f64> {
+    (0..=resolution).map(move |i| start + ((end - start) * i as f64 / resolution as f64))
+}
+
+fn z_up((x, y, z): Point3d) -> Point3d {
+    (x, z, y)
+}
+
+fn finite(p: Point3d) -> Option<Point3d> {
+    (p.0.is_finite() && p.1.is_finite() && p.2.is_finite()).then_some(p)
+}
+
+
+/// A parsed arithmetic expression such as `sin(x) * cos(y) / 2`.
+///
+/// Supports `+ - * / ^`, parentheses, the constants `pi` and `e`, and the functions
+/// `sin cos tan asin acos atan sqrt abs exp ln log floor ceil`, `min(a, b)`, `max(a, b)` and `atan2(y, x)`.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Number(f64),
+    Variable(usize),
+    Negate(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+    Call(String, Vec<Node>),
+}
+
+impl Expression {
+    /// Parses `source`, which may refer to the given `variables`.
+    pub fn parse(source: &str, variables: &[&str]) -> AppResult<Self> {
+        let mut parser = Parser { tokens: tokenize(source)?, position: 0, variables };
+        let root = parser.expression()?;
+        match parser.peek() {
+            None => Ok(Expression { root }),
+            Some(token) => Err(format!("unexpected '{token}' in expression").into()),
+        }
+    }
+
+    /// Evaluates the expression, `values` being the variables in the order they were given to [`Expression::parse`].
+    pub fn eval(&self, values: &[f64]) -> f64 {
+        self.root.eval(values)
+    }
+}
+
+impl Node {
+    fn eval(&self, values: &[f64]) -> f64 {
+        match self {
+            Node::Number(n) => *n,
+            Node::Variable(i) => values.get(*i).copied().unwrap_or(f64::NAN),
+            Node::Negate(a) => -a.eval(values),
+            Node::Binary(op, a, b) => {
+                let (a, b) = (a.eval(values), b.eval(values));
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    _ => a.powf(b),
+                }
+            }
+            Node::Call(name, arguments) => {
+                let a: Vec<f64> = arguments.iter().map(|n| n.eval(values)).collect();
+                match (name.as_str(), a.as_slice()) {
+                    ("sin", [a]) => a.sin(),
+                    ("cos", [a]) => a.cos(),
+                    ("tan", [a]) => a.tan(),
+                    ("asin", [a]) => a.asin(),
+                    ("acos", [a]) => a.acos(),
+                    ("atan", [a]) => a.atan(),
+                    ("sqrt", [a]) => a.sqrt(),
+                    ("abs", [a]) => a.abs(),
+                    ("exp", [a]) => a.exp(),
+                    ("ln", [a]) => a.ln(),
+                    ("log", [a]) => a.log10(),
+                    ("floor", [a]) => a.floor(),
+                    ("ceil", [a]) => a.ceil(),
+                    ("min", [a, b]) => a.min(*b),
+                    ("max", [a, b]) => a.max(*b),
+                    ("atan2", [a, b]) => a.atan2(*b),
+                    _ => f64::NAN,
+                }
+            }
+        }
+    }
+}
+
+const FUNCTIONS: [(&str, usize); 16] = [
+    ("sin", 1), ("cos", 1), ("tan", 1), ("asin", 1), ("acos", 1), ("atan", 1),
+    ("sqrt", 1), ("abs", 1), ("exp", 1), ("ln", 1), ("log", 1), ("floor", 1), ("ceil", 1),
+    ("min", 2), ("max", 2), ("atan2", 2),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Name(name) => write!(f, "{name}"),
+            Token::Symbol(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> AppResult<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') { i += 1 }
+            // exponent, as in 1e-3
+            if i < chars.len() && chars[i] == 'e' && chars.get(i + 1).is_some_and(|&n| n.is_ascii_digit() || n == '-' || n == '+') {
+                i += 2;
+                while i < chars.len() && chars[i].is_ascii_digit() { i += 1 }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text.parse().map_err(|_| format!("invalid number '{text}'"))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1 }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{c}' in expression").into());
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> AppResult<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("expected '{symbol}' in expression").into())
+        }
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> AppResult<Node> {
+        let mut node = self.term()?;
+        while let Some(op) = ['+', '-'].into_iter().find(|&op| self.eat(op)) {
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> AppResult<Node> {
+        let mut node = self.unary()?;
+        while let Some(op) = ['*', '/'].into_iter().find(|&op| self.eat(op)) {
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> AppResult<Node> {
+        if self.eat('-') {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    // power := atom ('^' unary)?
+    fn power(&mut self) -> AppResult<Node> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(Node::Binary('^', Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // atom := number | name | name '(' expression (',' expression)* ')' | '(' expression ')'
+    fn atom(&mut self) -> AppResult<Node> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Node::Number(n)),
+            Some(Token::Symbol('(')) => {
+                let node = self.expression()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Some(Token::Name(name)) if self.eat('(') => {
+                let mut arguments = vec![self.expression()?];
+                while self.eat(',') {
+                    arguments.push(self.expression()?);
+                }
+                self.expect(')')?;
+                match FUNCTIONS.iter().find(|(f, _)| *f == name) {
+                    Some((_, arity)) if *arity == arguments.len() => Ok(Node::Call(name, arguments)),
+                    Some((_, arity)) => Err(format!("{name} takes {arity} argument(s)").into()),
+                    None => Err(format!("unknown function '{name}'").into()),
+                }
+            }
+            Some(Token::Name(name)) => {
+                if let Some(i) = self.variables.iter().position(|v| *v == name) {
+                    Ok(Node::Variable(i))
+                } else {
+                    match name.as_str() {
+                        "pi" => Ok(Node::Number(std::f64::consts::PI)),
+                        "e" => Ok(Node::Number(std::f64::consts::E)),
+                        _ => Err(format!("unknown variable '{name}'").into()),
+                    }
+                }
+            }
+            Some(token) => Err(format!("unexpected '{token}' in expression").into()),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
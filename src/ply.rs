@@ -0,0 +1,306 @@
+use std::fs;
+use std::path::Path;
+use tui::style::Color;
+use crate::app::AppResult;
+use crate::pointcloud::{PointCloud, Rgb};
+use crate::scene::{Points3d, Polygon, Polyhedron, Transform};
+
+/// The contents of a PLY file: a list of elements such as `vertex` and `face`.
+#[derive(Clone, Debug, Default)]
+pub struct Ply {
+    pub elements: Vec<Element>,
+}
+
+/// One kind of element with its property names and a row of values per element.
+#[derive(Clone, Debug, Default)]
+pub struct Element {
+    pub name: String,
+    pub properties: Vec<String>,
+    /// Type of each property as declared in the header, such as `uchar` or `float`; the type of the items for lists.
+    pub types: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Value of a property, scalars widened to `f64`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+impl Ply {
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        Ply::parse(&fs::read(path)?)
+    }
+
+    /// Parses an ASCII, binary little endian or binary big endian PLY file.
+    pub fn parse(bytes: &[u8]) -> AppResult<Self> {
+        let end = find(bytes, b"end_header").ok_or("PLY header has no end_header")?;
+        let header = String::from_utf8_lossy(&bytes[..end]);
+        let mut body = &bytes[end + b"end_header".len()..];
+        // the body starts after the header's line break
+        while let Some((b, rest)) = body.split_first() {
+            body = rest;
+            if *b == b'\n' {
+                break;
+            }
+        }
+
+        let mut lines = header.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some("ply") {
+            return Err("not a PLY file".into());
+        }
+
+        let mut format: Option<Format> = None;
+        let mut layouts: Vec<(String, usize, Vec<Property>)> = Vec::new();
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["format", f, _] => format = Some(match *f {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::LittleEndian,
+                    "binary_big_endian" => Format::BigEndian,
+                    _ => return Err(format!("unsupported PLY format '{f}'").into()),
+                }),
+                ["element", name, count] => {
+                    let count = count.parse().map_err(|_| format!("invalid element count '{count}'"))?;
+                    layouts.push((name.to_string(), count, Vec::new()));
+                }
+                ["property", "list", count, item, name] => {
+                    let (_, _, properties) = layouts.last_mut().ok_or("PLY property outside an element")?;
+                    properties.push(Property {
+                        name: name.to_string(),
+                        declared: item.to_string(),
+                        kind: scalar(item)?,
+                        count: Some(scalar(count)?),
+                    });
+                }
+                ["property", kind, name] => {
+                    let (_, _, properties) = layouts.last_mut().ok_or("PLY property outside an element")?;
+                    properties.push(Property {
+                        name: name.to_string(),
+                        declared: kind.to_string(),
+                        kind: scalar(kind)?,
+                        count: None,
+                    });
+                }
+                ["comment", ..] | ["obj_info", ..] => {}
+                _ => return Err(format!("unexpected PLY header line '{line}'").into()),
+            }
+        }
+        let format = format.ok_or("PLY header has no format")?;
+
+        let mut reader: Box<dyn Reader> = match format {
+            Format::Ascii => Box::new(AsciiReader {
+                words: String::from_utf8_lossy(body).split_whitespace().map(String::from).collect::<Vec<_>>().into_iter(),
+            }),
+            Format::LittleEndian => Box::new(BinaryReader { bytes: body, big_endian: false }),
+            Format::BigEndian => Box::new(BinaryReader { bytes: body, big_endian: true }),
+        };
+
+        let mut elements = Vec::new();
+        for (name, count, properties) in layouts {
+            let mut rows = Vec::with_capacity(count.min(1 << 20));
+            for _ in 0..count {
+                let row = properties.iter().map(|p| match p.count {
+                    None => reader.read(p.kind).map(Value::Scalar),
+                    Some(count) => {
+                        let n = reader.read(count)? as usize;
+                        (0..n).map(|_| reader.read(p.kind)).collect::<AppResult<_>>().map(Value::List)
+                    }
+                }).collect::<AppResult<Vec<Value>>>()?;
+                rows.push(row);
+            }
+            elements.push(Element {
+                name,
+                types: properties.iter().map(|p| p.declared.clone()).collect(),
+                properties: properties.into_iter().map(|p| p.name).collect(),
+                rows,
+            });
+        }
+        Ok(Ply { elements })
+    }
+
+    pub fn element(&self, name: &str) -> Option<&Element> {
+        self.elements.iter().find(|e| e.name == name)
+    }
+
+    /// Builds a mesh from the `vertex` and `face` elements.
+    ///
+    /// Faces take the mean colour of their vertices when the vertices are coloured, and `color` otherwise.
+    /// A file without faces becomes a node holding a point cloud.
+    pub fn to_polyhedron(&self, color: Rgb) -> AppResult<Polyhedron> {
+        let vertex = self.element("vertex").ok_or("PLY file has no vertices")?;
+        let coordinate = |name| vertex.scalars(name).ok_or(format!("PLY vertices have no {name}"));
+        let (x, y, z) = (coordinate("x")?, coordinate("y")?, coordinate("z")?);
+        let vertices: Points3d = (0..x.len()).map(|i| (x[i], y[i], z[i])).collect();
+
+        let faces = self.element("face")
+            .and_then(|f| f.lists("vertex_indices").or_else(|| f.lists("vertex_index")));
+        let Some(faces) = faces else {
+            return Ok(Polyhedron::group(Vec::new()).with_point_cloud(PointCloud::from_ply(self, color)?));
+        };
+
+        let colors = vertex.colors();
+
+        let mut polygons = Vec::new();
+        for face in faces {
+            if let Some(&i) = face.iter().find(|&&i| i < 0.0 || i.fract() != 0.0 || i >= vertices.len() as f64) {
+                return Err(format!("PLY face refers to missing vertex {i}").into());
+            }
+            let indices: Vec<usize> = face.iter().map(|&i| i as usize).collect();
+            if indices.len() < 3 {
+                continue;
+            }
+            let face_color = match &colors {
+                Some(colors) => {
+                    let n = indices.len() as u32;
+                    let sum = indices.iter().fold((0u32, 0u32, 0u32), |(r, g, b), &i| {
+                        let (cr, cg, cb) = colors[i];
+                        (r + cr as u32, g + cg as u32, b + cb as u32)
+                    });
+                    Color::Rgb((sum.0 / n) as u8, (sum.1 / n) as u8, (sum.2 / n) as u8)
+                }
+                None => Color::Rgb(color.0, color.1, color.2),
+            };
+            polygons.push(Polygon::new(
+                indices.iter().map(|&i| vertices[i]).collect(),
+                face_color,
+                Transform::identity(),
+            ));
+        }
+        Ok(Polyhedron::new(polygons))
+    }
+}
+
+impl Element {
+    /// Index of the named property in the rows.
+    pub fn property(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|p| p == name)
+    }
+
+    /// The named scalar property of every row.
+    pub fn scalars(&self, name: &str) -> Option<Vec<f64>> {
+        let i = self.property(name)?;
+        self.rows.iter().map(|row| match row.get(i) {
+            Some(Value::Scalar(v)) => Some(*v),
+            _ => None,
+        }).collect()
+    }
+
+    /// The `red`, `green` and `blue` properties of every row, if there are all three.
+    ///
+    /// Channels declared as integers run from 0 to 255, and those declared as floating point from 0 to 1.
+    pub fn colors(&self) -> Option<Vec<Rgb>> {
+        let channel = |name: &str| -> Option<Vec<u8>> {
+            let float = matches!(self.types.get(self.property(name)?).map(String::as_str), Some("float" | "float32" | "double" | "float64"));
+            let values = self.scalars(name)?;
+            Some(values.into_iter().map(|v| if float { (v * 255.0).round() } else { v }.clamp(0.0, 255.0) as u8).collect())
+        };
+        let (r, g, b) = (channel("red")?, channel("green")?, channel("blue")?);
+        Some(r.into_iter().zip(g).zip(b).map(|((r, g), b)| (r, g, b)).collect())
+    }
+
+    /// The named list property of every row.
+    pub fn lists(&self, name: &str) -> Option<Vec<Vec<f64>>> {
+        let i = self.property(name)?;
+        self.rows.iter().map(|row| match row.get(i) {
+            Some(Value::List(v)) => Some(v.clone()),
+            _ => None,
+        }).collect()
+    }
+}
+
+enum Format {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum Scalar {
+    I8, U8, I16, U16, I32, U32, F32, F64,
+}
+
+impl Scalar {
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+struct Property {
+    name: String,
+    /// The type as declared, for [`Element::types`].
+    declared: String,
+    kind: Scalar,
+    /// Type of the length prefix of a list property.
+    count: Option<Scalar>,
+}
+
+fn scalar(name: &str) -> AppResult<Scalar> {
+    Ok(match name {
+        "char" | "int8" => Scalar::I8,
+        "uchar" | "uint8" => Scalar::U8,
+        "short" | "int16" => Scalar::I16,
+        "ushort" | "uint16" => Scalar::U16,
+        "int" | "int32" => Scalar::I32,
+        "uint" | "uint32" => Scalar::U32,
+        "float" | "float32" => Scalar::F32,
+        "double" | "float64" => Scalar::F64,
+        _ => return Err(format!("unknown PLY type '{name}'").into()),
+    })
+}
+
+trait Reader {
+    fn read(&mut self, kind: Scalar) -> AppResult<f64>;
+}
+
+struct AsciiReader {
+    words: std::vec::IntoIter<String>,
+}
+
+impl Reader for AsciiReader {
+    fn read(&mut self, _: Scalar) -> AppResult<f64> {
+        let word = self.words.next().ok_or("PLY data is truncated")?;
+        Ok(word.parse().map_err(|_| format!("invalid PLY value '{word}'"))?)
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader for BinaryReader<'_> {
+    fn read(&mut self, kind: Scalar) -> AppResult<f64> {
+        if self.bytes.len() < kind.size() {
+            return Err("PLY data is truncated".into());
+        }
+        let (value, rest) = self.bytes.split_at(kind.size());
+        self.bytes = rest;
+        let mut b = [0u8; 8];
+        b[..value.len()].copy_from_slice(value);
+        if self.big_endian {
+            b[..value.len()].reverse();
+        }
+        Ok(match kind {
+            Scalar::I8 => b[0] as i8 as f64,
+            Scalar::U8 => b[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(b),
+        })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tui::style::{Color, Style};
+use tui::symbols;
+use tui::widgets::{Dataset, GraphType};
+use crate::app::{AppResult, WorldMetrics};
+use crate::ply::Ply;
+use crate::scene::{camera_depth, project_to_screen, to_world, Point2d, Points2d, Points3d, Transform};
+
+/// Number of brightness steps used to shade points by their distance.
+const DEPTH_BANDS: usize = 8;
+
+/// Red, green and blue.
+pub type Rgb = (u8, u8, u8);
+
+/// A set of points drawn as single dots, e.g. a LIDAR scan or scatter data.
+///
+/// Points without a colour of their own are drawn in the cloud's colour,
+/// darker the farther they are from the camera.
+///
+/// Points are drawn in layers, one per colour, so that a large cloud takes a few datasets rather than one per point.
+/// This has two costs:
+/// - colours of their own are rounded down to 4 bits per channel (`0x1f` is drawn as `0x10`),
+///   so gradients finer than 16 steps show as bands;
+/// - layers are sorted by the mean depth of their points, not point by point, so a near point can be
+///   drawn behind a far one of another colour, and against other shapes it is in front or behind with its whole layer.
+#[derive(Clone, Debug)]
+pub struct PointCloud {
+    points: Points3d,
+    colors: Option<Vec<Rgb>>,
+    color: Rgb,
+    /// Projected points grouped by colour, with their mean depth.
+    layers: Vec<(f64, Color, Points2d)>,
+}
+
+impl PointCloud {
+    pub fn new(points: Points3d, color: Rgb) -> Self {
+        PointCloud { points, colors: None, color, layers: Vec::new() }
+    }
+
+    /// Gives every point its own colour; `colors` must be as long as the points.
+    pub fn with_colors(mut self, colors: Vec<Rgb>) -> Self {
+        self.colors = (colors.len() == self.points.len()).then_some(colors);
+        self
+    }
+
+    /// Reads a cloud from a CSV (`.csv`), XYZ (`.xyz`, `.txt`) or PLY (`.ply`) file.
+    ///
+    /// CSV and XYZ files hold one point per line, as `x y z` optionally followed by `r g b`,
+    /// separated by commas or whitespace. Lines that don't start with a number, such as headers, are skipped.
+    pub fn load(path: impl AsRef<Path>, color: Rgb) -> AppResult<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ply") => PointCloud::from_ply(&Ply::load(path)?, color),
+            _ => PointCloud::parse_text(&fs::read_to_string(path)?, color),
+        }
+    }
+
+    pub fn parse_text(text: &str, color: Rgb) -> AppResult<Self> {
+        let mut points = Vec::new();
+        let mut colors: Vec<(f64, f64, f64)> = Vec::new();
+        for line in text.lines() {
+            let values: Vec<f64> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .map_while(|v| v.parse().ok())
+                .collect();
+            match values.as_slice() {
+                [x, y, z, r, g, b, ..] => {
+                    points.push((*x, *y, *z));
+                    colors.push((*r, *g, *b));
+                }
+                [x, y, z, ..] => points.push((*x, *y, *z)),
+                _ => {}
+            }
+        }
+        if points.is_empty() {
+            return Err("point cloud has no points".into());
+        }
+        Ok(PointCloud::new(points, color).with_colors(to_rgb(&colors)))
+    }
+
+    pub fn from_ply(ply: &Ply, color: Rgb) -> AppResult<Self> {
+        let vertex = ply.element("vertex").ok_or("PLY file has no vertices")?;
+        let coordinate = |name| vertex.scalars(name).ok_or(format!("PLY vertices have no {name}"));
+        let (x, y, z) = (coordinate("x")?, coordinate("y")?, coordinate("z")?);
+        let points = (0..x.len()).map(|i| (x[i], y[i], z[i])).collect();
+
+        let mut cloud = PointCloud::new(points, color);
+        if let Some(colors) = vertex.colors() {
+            cloud = cloud.with_colors(colors);
+        }
+        Ok(cloud)
+    }
+
+    pub fn points(&self) -> &Points3d {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Projects the points of a cloud placed at `global`.
+    pub fn render(&mut self, world: &WorldMetrics, global: &Transform) {
+        let visible: Vec<(usize, f64, Point2d)> = self.points.iter().enumerate().filter_map(|(i, a)| {
+            let a = to_world(*a, world, global);
+            let depth = camera_depth(a, world);
+            (depth > world.near_plane).then(|| (i, depth, project_to_screen(a, world)))
+        }).collect();
+
+        let (near, far) = visible.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(near, far), (_, z, _)| {
+            (near.min(*z), far.max(*z))
+        });
+
+        let mut layers: BTreeMap<Rgb, (f64, Points2d)> = BTreeMap::new();
+        for (i, z, p) in visible {
+            let rgb = match &self.colors {
+                // coarser colours keep the number of layers down
+                Some(colors) => {
+                    let (r, g, b) = colors[i];
+                    (r & 0xf0, g & 0xf0, b & 0xf0)
+                }
+                None => {
+                    let t = if far > near { (z - near) / (far - near) } else { 0.0 };
+                    let band = ((t * DEPTH_BANDS as f64) as usize).min(DEPTH_BANDS - 1);
+                    let brightness = 1.0 - (0.7 * band as f64 / (DEPTH_BANDS - 1) as f64);
+                    let (r, g, b) = self.color;
+                    let dim = |c: u8| (c as f64 * brightness) as u8;
+                    (dim(r), dim(g), dim(b))
+                }
+            };
+            let (depth, points) = layers.entry(rgb).or_default();
+            *depth += z;
+            points.push(p);
+        }
+
+        self.layers = layers.into_iter().map(|((r, g, b), (depth, points))| {
+            (depth / points.len() as f64, Color::Rgb(r, g, b), points)
+        }).collect();
+    }
+
+    /// The projected points grouped by colour, each group with its mean depth.
+    pub fn layers(&self) -> &[(f64, Color, Points2d)] {
+        &self.layers
+    }
+
+    /// The projected points, one dataset per colour, each with its mean depth.
+    pub fn as_datasets(&self) -> Vec<(f64, Dataset<'_>)> {
+        self.layers.iter().map(|(depth, color, points)| {
+            (
+                *depth,
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Scatter)
+                    .style(Style::default().fg(*color))
+                    .data(points),
+            )
+        }).collect()
+    }
+}
+
+/// Colours with channels either all from 0 to 1 or all from 0 to 255.
+pub(crate) fn to_rgb(colors: &[(f64, f64, f64)]) -> Vec<Rgb> {
+    let unit = colors.iter().all(|(r, g, b)| r.max(*g).max(*b) <= 1.0);
+    let channel = |v: f64| if unit { (v * 255.0).round() as u8 } else { v.clamp(0.0, 255.0) as u8 };
+    colors.iter().map(|(r, g, b)| (channel(*r), channel(*g), channel(*b))).collect()
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use tui::style::Color;
+use crate::scene::{add, cross, dot, length, normalize, scale, sub, Point3d, Points3d, Polyhedron};
+
+/// Indexed faces of a mesh, each listing its vertices in winding order.
+type Faces = Vec<Vec<usize>>;
+
+/// A sphere made of `segments` meridians and `rings` parallels.
+pub fn uv_sphere(radius: f64, segments: usize, rings: usize, color: Color) -> Polyhedron {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+
+    let mut vertices: Points3d = vec![(0.0, radius, 0.0)]; // north pole
+    for r in 1..rings {
+        let polar = PI * r as f64 / rings as f64;
+        for s in 0..segments {
+            let azimuth = 2.0 * PI * s as f64 / segments as f64;
+            vertices.push((
+                radius * polar.sin() * azimuth.cos(),
+                radius * polar.cos(),
+                radius * polar.sin() * azimuth.sin(),
+            ));
+        }
+    }
+    let south = vertices.len();
+    vertices.push((0.0, -radius, 0.0));
+
+    let ring = |r: usize, s: usize| 1 + (r * segments) + (s % segments);
+    let mut faces: Faces = Vec::new();
+    for s in 0..segments {
+        faces.push(vec![0, ring(0, s + 1), ring(0, s)]);
+        faces.push(vec![south, ring(rings - 2, s), ring(rings - 2, s + 1)]);
+    }
+    for r in 0..rings - 2 {
+        for s in 0..segments {
+            faces.push(vec![ring(r, s), ring(r, s + 1), ring(r + 1, s + 1), ring(r + 1, s)]);
+        }
+    }
+    Polyhedron::from_mesh(&vertices, &outward(&vertices, faces), color)
+}
+
+/// A sphere made by splitting every triangle of an icosahedron into four, `subdivisions` times.
+pub fn ico_sphere(radius: f64, subdivisions: usize, color: Color) -> Polyhedron {
+    let (mut vertices, mut faces) = icosahedron_mesh();
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, vertices: &mut Points3d| -> usize {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push(normalize(scale(add(vertices[a], vertices[b]), 0.5)));
+                vertices.len() - 1
+            })
+        };
+        faces = faces.iter().flat_map(|f| {
+            let (a, b, c) = (f[0], f[1], f[2]);
+            let ab = midpoint(a, b, &mut vertices);
+            let bc = midpoint(b, c, &mut vertices);
+            let ca = midpoint(c, a, &mut vertices);
+            vec![vec![a, ab, ca], vec![b, bc, ab], vec![c, ca, bc], vec![ab, bc, ca]]
+        }).collect();
+    }
+    let vertices: Points3d = vertices.into_iter().map(|v| scale(v, radius)).collect();
+    Polyhedron::from_mesh(&vertices, &faces, color)
+}
+
+/// A ring around the y axis; `segments` go around the ring and `sides` around the tube.
+pub fn torus(major_radius: f64, minor_radius: f64, segments: usize, sides: usize, color: Color) -> Polyhedron {
+    let segments = segments.max(3);
+    let sides = sides.max(3);
+
+    let mut vertices: Points3d = Vec::new();
+    for s in 0..segments {
+        let u = 2.0 * PI * s as f64 / segments as f64;
+        for t in 0..sides {
+            let v = 2.0 * PI * t as f64 / sides as f64;
+            let r = major_radius + minor_radius * v.cos();
+            vertices.push((r * u.cos(), minor_radius * v.sin(), r * u.sin()));
+        }
+    }
+
+    let index = |s: usize, t: usize| ((s % segments) * sides) + (t % sides);
+    let mut faces: Faces = Vec::new();
+    for s in 0..segments {
+        for t in 0..sides {
+            faces.push(vec![index(s, t), index(s, t + 1), index(s + 1, t + 1), index(s + 1, t)]);
+        }
+    }
+    Polyhedron::from_mesh(&vertices, &faces, color)
+}
+
+/// A capped cylinder standing on the y axis, centered on the origin.
+pub fn cylinder(radius: f64, height: f64, segments: usize, color: Color) -> Polyhedron {
+    let segments = segments.max(3);
+    let h = height / 2.0;
+
+    let mut vertices: Points3d = circle(radius, segments, h);
+    vertices.append(&mut circle(radius, segments, -h));
+
+    let mut faces: Faces = (0..segments).map(|s| {
+        let n = (s + 1) % segments;
+        vec![s, n, segments + n, segments + s]
+    }).collect();
+    faces.push((0..segments).collect());
+    faces.push((segments..2 * segments).rev().collect());
+    Polyhedron::from_mesh(&vertices, &outward(&vertices, faces), color)
+}
+
+/// A cone standing on the y axis with its base and apex `height` apart, centered on the origin.
+pub fn cone(radius: f64, height: f64, segments: usize, color: Color) -> Polyhedron {
+    let segments = segments.max(3);
+    let h = height / 2.0;
+
+    let mut vertices: Points3d = circle(radius, segments, -h);
+    let apex = vertices.len();
+    vertices.push((0.0, h, 0.0));
+
+    let mut faces: Faces = (0..segments).map(|s| vec![s, (s + 1) % segments, apex]).collect();
+    faces.push((0..segments).rev().collect());
+    Polyhedron::from_mesh(&vertices, &outward(&vertices, faces), color)
+}
+
+/// A flat `size` by `size` grid in the xz plane, split into `segments` cells along each side.
+pub fn plane(size: f64, segments: usize, color: Color) -> Polyhedron {
+    let segments = segments.max(1);
+    let step = size / segments as f64;
+    let h = size / 2.0;
+
+    let mut vertices: Points3d = Vec::new();
+    for z in 0..=segments {
+        for x in 0..=segments {
+            vertices.push((x as f64 * step - h, 0.0, z as f64 * step - h));
+        }
+    }
+
+    let index = |x: usize, z: usize| (z * (segments + 1)) + x;
+    let mut faces: Faces = Vec::new();
+    for z in 0..segments {
+        for x in 0..segments {
+            faces.push(vec![index(x, z), index(x + 1, z), index(x + 1, z + 1), index(x, z + 1)]);
+        }
+    }
+    Polyhedron::from_mesh(&vertices, &faces, color)
+}
+
+/// A regular tetrahedron with its vertices `size` away from the center.
+pub fn tetrahedron(size: f64, color: Color) -> Polyhedron {
+    let vertices: Points3d = vec![
+        ( 1.0,  1.0,  1.0),
+        ( 1.0, -1.0, -1.0),
+        (-1.0,  1.0, -1.0),
+        (-1.0, -1.0,  1.0),
+    ];
+    let faces: Faces = vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]];
+    platonic(vertices, faces, size, color)
+}
+
+/// A cube with its vertices `size` away from the center.
+pub fn cube(size: f64, color: Color) -> Polyhedron {
+    let vertices: Points3d = (0..8).map(|i| {
+        let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+        (sign(1), sign(2), sign(4))
+    }).collect();
+    let faces: Faces = vec![
+        vec![0, 1, 3, 2],
+        vec![4, 5, 7, 6],
+        vec![0, 1, 5, 4],
+        vec![2, 3, 7, 6],
+        vec![0, 2, 6, 4],
+        vec![1, 3, 7, 5],
+    ];
+    platonic(vertices, faces, size, color)
+}
+
+/// A regular octahedron with its vertices `size` away from the center.
+pub fn octahedron(size: f64, color: Color) -> Polyhedron {
+    let vertices: Points3d = vec![
+        ( 1.0, 0.0, 0.0), (-1.0, 0.0, 0.0),
+        (0.0,  1.0, 0.0), (0.0, -1.0, 0.0),
+        (0.0, 0.0,  1.0), (0.0, 0.0, -1.0),
+    ];
+    let faces = triangles_of_shortest_edges(&vertices);
+    platonic(vertices, faces, size, color)
+}
+
+/// A regular dodecahedron with its vertices `size` away from the center.
+pub fn dodecahedron(size: f64, color: Color) -> Polyhedron {
+    let (vertices, faces) = icosahedron_mesh();
+    let (vertices, faces) = dual(&vertices, &faces);
+    platonic(vertices, faces, size, color)
+}
+
+/// A regular icosahedron with its vertices `size` away from the center.
+pub fn icosahedron(size: f64, color: Color) -> Polyhedron {
+    let (vertices, faces) = icosahedron_mesh();
+    platonic(vertices, faces, size, color)
+}
+
+fn platonic(vertices: Points3d, faces: Faces, size: f64, color: Color) -> Polyhedron {
+    let vertices: Points3d = vertices.into_iter().map(|v| scale(normalize(v), size)).collect();
+    Polyhedron::from_mesh(&vertices, &outward(&vertices, faces), color)
+}
+
+/// Unit icosahedron with outward facing triangles.
+fn icosahedron_mesh() -> (Points3d, Faces) {
+    let phi = (1.0 + 5.0f64.sqrt())/2.0;
+    let mut vertices: Points3d = Vec::new();
+    for a in [-1.0, 1.0] {
+        for b in [-phi, phi] {
+            vertices.push((0.0, a, b));
+            vertices.push((a, b, 0.0));
+            vertices.push((b, 0.0, a));
+        }
+    }
+    let faces = triangles_of_shortest_edges(&vertices);
+    let vertices: Points3d = vertices.into_iter().map(normalize).collect();
+    let faces = outward(&vertices, faces);
+    (vertices, faces)
+}
+
+/// Every triangle whose three sides are all as short as the shortest edge of the point set.
+fn triangles_of_shortest_edges(vertices: &Points3d) -> Faces {
+    let n = vertices.len();
+    let distance = |a: usize, b: usize| length(sub(vertices[a], vertices[b]));
+    let shortest = (0..n)
+        .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+        .map(|(a, b)| distance(a, b))
+        .fold(f64::INFINITY, f64::min);
+    let is_edge = |a: usize, b: usize| (distance(a, b) - shortest).abs() < 1e-9;
+
+    let mut faces: Faces = Vec::new();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                if is_edge(a, b) && is_edge(b, c) && is_edge(a, c) {
+                    faces.push(vec![a, b, c]);
+                }
+            }
+        }
+    }
+    faces
+}
+
+/// Swaps the faces' vertices to the dual polyhedron's: one vertex per face, one face per vertex.
+fn dual(vertices: &Points3d, faces: &Faces) -> (Points3d, Faces) {
+    let centers: Points3d = faces.iter().map(|f| centroid(vertices, f)).collect();
+    let dual_faces: Faces = (0..vertices.len()).map(|v| {
+        let axis = normalize(vertices[v]);
+        let mut around: Vec<usize> = (0..faces.len()).filter(|&f| faces[f].contains(&v)).collect();
+        let reference = sub(centers[around[0]], vertices[v]);
+        around.sort_by(|&a, &b| {
+            let angle = |f: usize| {
+                let d = sub(centers[f], vertices[v]);
+                dot(cross(reference, d), axis).atan2(dot(reference, d))
+            };
+            angle(a).partial_cmp(&angle(b)).unwrap()
+        });
+        around
+    }).collect();
+    (centers, dual_faces)
+}
+
+/// Reverses the faces that wind clockwise when seen from outside a shape centered on the origin.
+fn outward(vertices: &Points3d, faces: Faces) -> Faces {
+    faces.into_iter().map(|mut f| {
+        let (a, b, c) = (vertices[f[0]], vertices[f[1]], vertices[f[2]]);
+        let normal = cross(sub(b, a), sub(c, a));
+        if dot(normal, centroid(vertices, &f)) < 0.0 {
+            f.reverse();
+        }
+        f
+    }).collect()
+}
+
+fn centroid(vertices: &Points3d, face: &[usize]) -> Point3d {
+    let sum = face.iter().fold((0.0, 0.0, 0.0), |acc, &i| add(acc, vertices[i]));
+    scale(sum, 1.0 / face.len() as f64)
+}
+
+fn circle(radius: f64, segments: usize, y: f64) -> Points3d {
+    (0..segments).map(|s| {
+        let angle = 2.0 * PI * s as f64 / segments as f64;
+        (radius * angle.cos(), y, radius * angle.sin())
+    }).collect()
+}
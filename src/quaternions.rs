@@ -32,7 +32,7 @@ impl Quaternion {
             (q.a * p.a) - (q.b * p.b) - (q.c * p.c) - (q.d * p.d),
             (q.a * p.b) + (q.b * p.a) + (q.c * p.d) - (q.d * p.c), 
             (q.a * p.c) - (q.b * p.d) + (q.c * p.a) + (q.d * p.b), 
-            (q.a * p.d) + (q.b * p.c) - (q.c * p.b) - (q.d * p.a)
+            (q.a * p.d) + (q.b * p.c) - (q.c * p.b) + (q.d * p.a)
         )
     }
 
@@ -48,21 +48,39 @@ impl Quaternion {
         ((self.a*self.a) + (self.b*self.b) + (self.c*self.c) + (self.d*self.d)).sqrt()
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.len() == 0.0
+    }
+
+    /// The quaternion scaled to length 1; the zero quaternion has no direction and stays zero.
     pub fn unitize(&self) -> Quaternion {
+        if self.is_zero() {
+            return self.clone();
+        }
         let l = self.len();
         self.clone() * (1.0/l)
     }
 
+    /// The quaternion `q⁻¹` with `q * q⁻¹ = 1`; the zero quaternion has none and gives zero rather than NaN.
     pub fn inverse(&self) -> Quaternion {
+        if self.is_zero() {
+            return self.clone();
+        }
         let qi = self.conjugate();
         let l = self.len();
         let l2 = l*l;
         qi*(1.0/l2)
     }
+
+    /// The unit quaternion turning `theta` radians around this quaternion's vector part,
+    /// no turn at all when the vector part is zero.
     pub fn rotatation(&self, theta: f64) -> Quaternion {
+        let u = Quaternion::new(0.0, self.b, self.c, self.d).unitize();
+        if u.is_zero() {
+            return Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        }
         let c = (theta/2.0).cos();
         let s =  (theta/2.0).sin();
-        let u = self.unitize();
         (u * s) + c
     }
 
@@ -121,8 +139,8 @@ impl From<(f64,f64,f64)> for Quaternion {
     }
 }
 
-impl Into<(f64,f64,f64)> for Quaternion {
-    fn into(self) -> (f64,f64,f64) {
-        (self.b, self.c, self.d)
+impl From<Quaternion> for (f64,f64,f64) {
+    fn from(value: Quaternion) -> Self {
+        (value.b, value.c, value.d)
     }
 }
\ No newline at end of file
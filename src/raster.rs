@@ -0,0 +1,182 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use crate::app::{App, AppResult, Shape};
+use crate::color::to_rgb;
+use crate::pointcloud::Rgb;
+use crate::scene::Point2d;
+use crate::svg::ImageOptions;
+
+/// An RGB framebuffer; pixels nothing was drawn on have no colour.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Rows from the top, each from the left.
+    pub pixels: Vec<Option<Rgb>>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Option<Rgb>) -> Self {
+        Image { width, height, pixels: vec![background; width as usize * height as usize] }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Rgb> {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Colours the pixel at `(x, y)`, if it is inside the image.
+    pub fn set(&mut self, x: i64, y: i64, color: Rgb) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            self.pixels[(y as u32 * self.width + x as u32) as usize] = Some(color);
+        }
+    }
+
+    /// Draws a round dot `width` pixels across.
+    pub fn dot(&mut self, (x, y): Point2d, width: f64, color: Rgb) {
+        let r = (width / 2.0).max(0.5);
+        for py in (y - r).floor() as i64..=(y + r).ceil() as i64 {
+            for px in (x - r).floor() as i64..=(x + r).ceil() as i64 {
+                let (dx, dy) = (px as f64 + 0.5 - x, py as f64 + 0.5 - y);
+                if dx * dx + dy * dy <= r * r {
+                    self.set(px, py, color);
+                }
+            }
+        }
+    }
+
+    /// Draws a straight line `width` pixels wide.
+    pub fn line(&mut self, from: Point2d, to: Point2d, width: f64, color: Rgb) {
+        let margin = width;
+        let bounds = (-margin, -margin, self.width as f64 + margin, self.height as f64 + margin);
+        let Some(((x0, y0), (x1, y1))) = clip(from, to, bounds) else {
+            return;
+        };
+        let steps = ((x1 - x0).abs().max((y1 - y0).abs()) * 2.0).ceil().clamp(1.0, 1e6) as usize;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            self.dot((x0 + t * (x1 - x0), y0 + t * (y1 - y0)), width, color);
+        }
+    }
+
+    /// Fills the inside of a polygon, where an odd number of its sides are to the left.
+    pub fn fill(&mut self, corners: &[Point2d], color: Rgb) {
+        if corners.len() < 3 {
+            return;
+        }
+        let (top, bottom) = corners.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(t, b), (_, y)| (t.min(*y), b.max(*y)));
+        let rows = (top.floor().max(0.0) as i64)..=(bottom.ceil().min(self.height as f64) as i64);
+        for py in rows {
+            // through the middle of the row of pixels
+            let y = py as f64 + 0.5;
+            let mut crossings: Vec<f64> = Vec::new();
+            for i in 0..corners.len() {
+                let (x0, y0) = corners[i];
+                let (x1, y1) = corners[(i + 1) % corners.len()];
+                if (y0 <= y) != (y1 <= y) {
+                    crossings.push(x0 + (y - y0) * (x1 - x0) / (y1 - y0));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            for span in crossings.chunks_exact(2) {
+                let from = (span[0] - 0.5).ceil().max(0.0) as i64;
+                let to = (span[1] - 0.5).floor().min(self.width as f64) as i64;
+                for px in from..=to {
+                    self.set(px, py, color);
+                }
+            }
+        }
+    }
+
+    /// The image as a binary PPM (P6); pixels without colour are black.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            let (r, g, b) = pixel.unwrap_or((0, 0, 0));
+            bytes.extend([r, g, b]);
+        }
+        bytes
+    }
+
+    /// Writes the image as a PNG; pixels without colour are transparent.
+    pub fn write_png(&self, writer: impl Write) -> AppResult<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels.iter().flat_map(|pixel| match pixel {
+            Some((r, g, b)) => [*r, *g, *b, 255],
+            None => [0, 0, 0, 0],
+        }).collect();
+        encoder.write_header()?.write_image_data(&data)?;
+        Ok(())
+    }
+
+    /// Writes the image as a PNG (`.png`) or PPM (`.ppm`) file.
+    pub fn save(&self, path: impl AsRef<Path>) -> AppResult<()> {
+        let path = path.as_ref();
+        let result = match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => File::create(path).map_err(Into::into).and_then(|f| self.write_png(BufWriter::new(f))),
+            Some("ppm") => std::fs::write(path, self.to_ppm()).map_err(Into::into),
+            _ => Err("unsupported image format, expected .png or .ppm".into()),
+        };
+        result.map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+}
+
+/// The part of the line from `a` to `b` inside the box from `(left, top)` to `(right, bottom)`.
+fn clip(a: Point2d, b: Point2d, (left, top, right, bottom): (f64, f64, f64, f64)) -> Option<(Point2d, Point2d)> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    // Liang-Barsky: narrow the range of t at each edge of the box
+    for (p, q) in [(-dx, a.0 - left), (dx, right - a.0), (-dy, a.1 - top), (dy, bottom - a.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    (t0 <= t1).then_some(((a.0 + t0 * dx, a.1 + t0 * dy), (a.0 + t1 * dx, a.1 + t1 * dy)))
+}
+
+/// Draws the scene, as the camera of `app` sees it, into an image of the size in `options`.
+///
+/// Polygons become outlines, or filled shapes when not drawing wireframes, and point clouds become dots.
+pub fn rasterize(app: &mut App, options: &ImageOptions) -> Image {
+    app.project_scene();
+    let mut image = Image::new(options.width, options.height, options.background.map(to_rgb));
+    let pixels = |points: &[Point2d]| -> Vec<Point2d> { points.iter().map(|p| options.to_pixels(*p)).collect() };
+    for shape in app.shapes() {
+        match shape {
+            Shape::Polygon(p) => {
+                let color = to_rgb(p.color());
+                // the vertices, unless some are behind the camera
+                let corners = !p.corners().is_empty();
+                let outline = pixels(if corners { p.corners() } else { p.projection() });
+                if p.is_closed() && corners && !app.wireframe {
+                    image.fill(&outline, color);
+                }
+                for side in outline.windows(2) {
+                    image.line(side[0], side[1], options.line_width, color);
+                }
+                if let (true, Some(first), Some(last)) = (p.is_closed() && corners, outline.first(), outline.last()) {
+                    image.line(*last, *first, options.line_width, color);
+                }
+            }
+            Shape::Points(color, cloud) => {
+                for p in pixels(cloud) {
+                    image.dot(p, 2.0 * options.line_width, to_rgb(color));
+                }
+            }
+        }
+    }
+    image
+}
+
+/// Writes the scene, as the camera of `app` sees it, to a PNG (`.png`) or PPM (`.ppm`) file.
+pub fn export(app: &mut App, path: impl AsRef<Path>, options: &ImageOptions) -> AppResult<()> {
+    rasterize(app, options).save(path)
+}
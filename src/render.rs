@@ -0,0 +1,126 @@
+use std::str::FromStr;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::symbols::{self, Marker};
+use tui::widgets::{Chart, Widget};
+
+const UPPER_HALF: &str = "▀";
+const LOWER_HALF: &str = "▄";
+
+/// How projected points are drawn into terminal cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Renderer {
+    /// Braille dots, 2 by 4 points per cell.
+    #[default]
+    Braille,
+    /// Plain ASCII characters, one point per cell.
+    Ascii,
+    /// Upper and lower half blocks, 1 by 2 points per cell.
+    HalfBlock,
+}
+
+impl Renderer {
+    /// Marker the chart datasets are drawn with.
+    pub fn marker(self) -> Marker {
+        match self {
+            Renderer::Braille => Marker::Braille,
+            Renderer::Ascii => Marker::Dot,
+            Renderer::HalfBlock => Marker::Block,
+        }
+    }
+
+    /// Points per cell across and down.
+    pub fn resolution(self) -> (u16, u16) {
+        match self {
+            Renderer::Braille => (2, 4),
+            Renderer::Ascii => (1, 1),
+            Renderer::HalfBlock => (1, 2),
+        }
+    }
+}
+
+impl FromStr for Renderer {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "braille" => Ok(Renderer::Braille),
+            "ascii" => Ok(Renderer::Ascii),
+            "halfblock" => Ok(Renderer::HalfBlock),
+            _ => Err(format!("unknown renderer '{name}', expected braille, ascii or halfblock")),
+        }
+    }
+}
+
+/// A chart drawn with [`Marker::Dot`], its dots and lines replaced by ASCII characters.
+pub struct Ascii<'a>(pub Chart<'a>);
+
+impl Widget for Ascii<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.0.render(area, buf);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                let symbol = match cell.symbol.as_str() {
+                    symbols::DOT => "*",
+                    symbols::line::VERTICAL => "|",
+                    symbols::line::HORIZONTAL => "-",
+                    symbols::line::BOTTOM_LEFT => "+",
+                    s if s.is_ascii() => continue,
+                    _ => "#",
+                };
+                cell.set_symbol(symbol);
+            }
+        }
+    }
+}
+
+/// Points drawn as half blocks, twice as fine vertically as [`Marker::Block`].
+///
+/// `chart` provides the title and axes, and `plot` the datasets, drawn with [`Marker::Block`]
+/// and with the same bounds as `chart` but without labels.
+pub struct HalfBlocks<'a> {
+    pub chart: Chart<'a>,
+    pub plot: Chart<'a>,
+}
+
+impl Widget for HalfBlocks<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.chart.render(area, buf);
+        let graph = graph_area(area, buf);
+
+        // one row of the plot per half cell
+        let mut plot = Buffer::empty(Rect::new(0, 0, graph.width, graph.height * 2));
+        self.plot.render(plot.area, &mut plot);
+        let half = |x: u16, y: u16| {
+            let cell = plot.get(x, y);
+            (cell.symbol != " ").then_some(cell.fg)
+        };
+
+        for y in 0..graph.height {
+            for x in 0..graph.width {
+                let cell = buf.get_mut(graph.x + x, graph.y + y);
+                match (half(x, 2 * y), half(x, 2 * y + 1)) {
+                    (Some(top), Some(bottom)) if top == bottom => cell.set_symbol(symbols::block::FULL).set_fg(top),
+                    (Some(top), Some(bottom)) => cell.set_symbol(UPPER_HALF).set_fg(top).set_bg(bottom),
+                    (Some(top), None) => cell.set_symbol(UPPER_HALF).set_fg(top),
+                    (None, Some(bottom)) => cell.set_symbol(LOWER_HALF).set_fg(bottom),
+                    (None, None) => cell,
+                };
+            }
+        }
+    }
+}
+
+/// Where a chart with a title but no borders drew its points: right of and above where its axes meet.
+fn graph_area(area: Rect, buf: &Buffer) -> Rect {
+    let inner = Rect::new(area.x, area.y + 1.min(area.height), area.width, area.height.saturating_sub(1));
+    for y in inner.top()..inner.bottom() {
+        for x in inner.left()..inner.right() {
+            if buf.get(x, y).symbol == symbols::line::BOTTOM_LEFT {
+                return Rect::new(x + 1, inner.y, inner.right() - x - 1, y - inner.y);
+            }
+        }
+    }
+    inner
+}
@@ -0,0 +1,525 @@
+use std::f64::consts::PI;
+use tui::style::{Color, Style};
+use tui::symbols;
+use tui::widgets::{Dataset, GraphType};
+use crate::app::WorldMetrics;
+use crate::pointcloud::PointCloud;
+use crate::quaternions::Quaternion;
+
+pub type Point3d = (f64,f64,f64);
+pub type Point2d = (f64,f64);
+
+pub type Points3d = Vec<(f64,f64,f64)>;
+pub type Points2d = Vec<(f64,f64)>;
+
+/// Anything closer to the camera than this is not drawn, unless configured otherwise.
+pub const NEAR_PLANE: f64 = 1.0;
+
+/// Points along each side of a polygon, unless configured otherwise.
+pub const LINE_POINTS: usize = 200;
+
+pub struct Line{
+    points: Points3d,
+    center: Point3d,
+}
+
+impl Line{
+    /// A line from `start` to `end`, drawn with `points` points.
+    pub fn new(start: Point3d, end: Point3d, points: usize) -> Line{
+        Line{
+            points: Line::interpolate(&start, &end, points),
+            center: Line::center_point_of(&start, &end),
+        }
+    }
+
+    fn center_point_of(a: &Point3d, b: &Point3d) -> Point3d {
+        let (a0, a1, a2) = a;
+        let (b0, b1, b2) = b;
+        let c = |a,b| -> f64 {(a+b)/2.0};
+        (c(a0, b0),c(a1, b1),c(a2, b2))
+    }
+
+    pub fn interpolate(start: &Point3d, end: &Point3d, points: usize) -> Points3d {
+        let resolution = 1.0 / points as f64;
+        let (endx, endy, endz) = end;
+        let (startx, starty, startz) = start;
+        let (deltax, deltay, deltaz): Point3d = ((endx-startx)*resolution,(endy-starty)*resolution,(endz-startz)*resolution);
+
+        let data : Points3d = (0..points).map(|i|{
+            (
+                (startx + (deltax*(i as f64))),
+                (starty + (deltay*(i as f64))),
+                (startz + (deltaz*(i as f64))),
+            )
+        }).collect();
+        data
+    }
+}
+
+/// Position and orientation of a scene node relative to its parent.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    pub translation: Point3d,
+    /// Unit quaternion, see [`Quaternion::rotatation`].
+    pub rotation: Quaternion,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn new(translation: Point3d, rotation: Quaternion) -> Self {
+        Transform { translation, rotation }
+    }
+
+    pub fn identity() -> Self {
+        Transform::new((0.0,0.0,0.0), Quaternion::new(1.0,0.0,0.0,0.0))
+    }
+
+    /// A transform turned `theta` radians around `axis`.
+    pub fn from_axis_angle(translation: Point3d, axis: Quaternion, theta: f64) -> Self {
+        Transform::new(translation, axis.rotatation(theta))
+    }
+
+    /// Moves a point from this transform's local space into its parent's space.
+    pub fn apply(&self, a: Point3d) -> Point3d {
+        let q = self.rotation.clone();
+        let qi = q.inverse();
+        let (x, y, z) = (q * Quaternion::from(a) * qi).into();
+        let (tx, ty, tz) = self.translation;
+        (x + tx, y + ty, z + tz)
+    }
+
+    /// The transform of a child with the `local` transform, expressed in this transform's parent space.
+    pub fn compose(&self, local: &Transform) -> Transform {
+        Transform::new(
+            self.apply(local.translation),
+            self.rotation.clone() * local.rotation.clone(),
+        )
+    }
+}
+
+/// Motion of a scene node over time, on top of its transform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Animation {
+    /// Turns around `axis` at `speed` radians per second.
+    Spin { axis: Point3d, speed: f64 },
+    /// Swings back and forth by up to `offset`, once every `period` seconds.
+    Oscillate { offset: Point3d, period: f64 },
+}
+
+impl Animation {
+    /// Where the animation has got to `time` milliseconds in.
+    pub fn at(&self, time: f64) -> Transform {
+        let seconds = time / 1000.0;
+        match self {
+            Animation::Spin { axis, speed } => Transform::from_axis_angle((0.0,0.0,0.0), Quaternion::from(*axis), (speed * seconds) % (2.0*PI)),
+            Animation::Oscillate { offset, period } => {
+                let phase = if *period > 0.0 { (2.0*PI*seconds / period).sin() } else { 0.0 };
+                Transform::new(scale(*offset, phase), Quaternion::new(1.0,0.0,0.0,0.0))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Polygon{
+    projection: Points2d,
+    /// Projected vertices, empty when some are behind the camera.
+    corners: Points2d,
+    vertices: Points3d,
+    points: Points3d,
+    center /*of gravity, in world space*/: Point3d,
+    color: Color,
+    transform: Transform,
+    /// Whether the last vertex joins back to the first.
+    closed: bool,
+}
+
+
+impl Polygon {
+    pub fn new(vertices: Points3d, color : Color, transform: Transform) -> Self{
+        Self{
+            vertices,
+            center: (0.0f64,0.0f64,0.0f64),
+            points: Vec::new(),
+            color,
+            transform,
+            projection: Vec::new(),
+            corners: Vec::new(),
+            closed: true,
+        }
+    }
+
+    /// Constructs an open path through `vertices`, e.g. a curve.
+    pub fn polyline(vertices: Points3d, color : Color, transform: Transform) -> Self{
+        Self{
+            closed: false,
+            ..Self::new(vertices, color, transform)
+        }
+    }
+
+    pub fn center(&self) -> Point3d {
+        self.center
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// The vertices, in the polygon's own space.
+    pub fn vertices(&self) -> &Points3d {
+        &self.vertices
+    }
+
+    /// Whether the last vertex joins back to the first.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The projected points along the sides.
+    pub fn projection(&self) -> &Points2d {
+        &self.projection
+    }
+
+    /// The projected vertices, empty when some are behind the camera.
+    pub fn corners(&self) -> &Points2d {
+        &self.corners
+    }
+
+    fn generate_sides_and_center(&mut self, line_points: usize) -> Vec<Line> {
+        let (mut c0, mut c1, mut c2) = (0.0, 0.0, 0.0);
+        let mut sides : Vec<Line> = Vec::new();
+        for i in 1..self.vertices.len() { //
+            let l = Line::new(
+                self.vertices[i-1],
+                self.vertices[i],
+                line_points,
+            );
+            let (l0, l1, l2) = l.center;   // centering
+            c0 += l0; c1 += l1; c2 += l2; // centering
+            sides.push(l);
+        }
+        if self.closed {
+            let l = Line::new(
+                self.vertices[self.vertices.len()-1],
+                self.vertices[0],
+                line_points,
+            );
+            let (l0, l1, l2) = l.center; // centering
+            c0 += l0; c1 += l1; c2 += l2; // centering
+
+            sides.push(l);
+        }
+
+        let n = sides.len() as f64; // centering
+        self.center = (c0/n, c1/n, c2/n); // centering
+
+        sides
+    }
+
+
+    fn generate_points(&mut self, line_points: usize) {
+        let sides = self.generate_sides_and_center(line_points);
+        let mut points : Points3d = Vec::new();
+        for mut side in sides {
+            points.append(&mut side.points);
+        }
+        self.points = points;
+    }
+
+    pub fn as_dataset(&self) -> Dataset<'_> {
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(self.color))
+            .data(&self.projection)
+    }
+
+    /// Points covering the inside of the projected polygon, `step` apart along each axis.
+    ///
+    /// Open polylines, and polygons reaching behind the camera, have no inside.
+    pub fn fill(&self, (step_x, step_y): Point2d) -> Points2d {
+        let corners = &self.corners;
+        let mut points = Vec::new();
+        if !self.closed || corners.len() < 3 {
+            return points;
+        }
+        let (bottom, top) = corners.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(b, t), (_, y)| (b.min(*y), t.max(*y)));
+        let mut y = (bottom / step_y).ceil() * step_y;
+        while y <= top {
+            let mut crossings: Vec<f64> = Vec::new();
+            for i in 0..corners.len() {
+                let (x0, y0) = corners[i];
+                let (x1, y1) = corners[(i + 1) % corners.len()];
+                if (y0 <= y) != (y1 <= y) {
+                    crossings.push(x0 + (y - y0) * (x1 - x0) / (y1 - y0));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            for span in crossings.chunks_exact(2) {
+                let mut x = (span[0] / step_x).ceil() * step_x;
+                while x <= span[1] {
+                    points.push((x, y));
+                    x += step_x;
+                }
+            }
+            y += step_y;
+        }
+        points
+    }
+
+    /// Renders the polygon as a child of a node placed at `parent`.
+    pub fn render(&mut self, world: &WorldMetrics, parent: &Transform){
+        let global = parent.compose(&self.transform);
+        self.generate_points(world.line_points);
+        self.transform(world, &global);
+        self.project(world);
+        self.corners = self.vertices.iter()
+            .map(|a| to_world(*a, world, &global))
+            .map(|a| (camera_depth(a, world) > world.near_plane).then(|| project_to_screen(a, world)))
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+    }
+
+    fn project(&mut self, world: &WorldMetrics){
+        // points behind the camera would come out mirrored
+        self.projection = self.points.iter()
+            .filter(|a| camera_depth(**a, world) > world.near_plane)
+            .map(|a| project_to_screen(*a, world))
+            .collect()
+    }
+
+    fn transform(&mut self, world: &WorldMetrics, global: &Transform) {
+        for a in self.points.iter_mut() {
+            *a = to_world(*a, world, global);
+        }
+        self.center = to_world(self.center, world, global);
+    }
+
+}
+
+
+/// A node in the scene graph.
+///
+/// The polygons and the child nodes are placed relative to the node's own
+/// [`Transform`], so moving or turning a node carries everything below it along.
+/// A node without polygons acts as a plain group.
+#[derive(Debug, Default)]
+pub struct Polyhedron {
+    polygons: Vec<Polygon>,
+    point_clouds: Vec<PointCloud>,
+    transform: Transform,
+    animation: Option<Animation>,
+    children: Vec<Polyhedron>,
+}
+
+
+impl Polyhedron {
+    pub fn new(polygons: Vec<Polygon>) -> Self {
+        Polyhedron {
+            polygons,
+            ..Default::default()
+        }
+    }
+
+    /// Constructs a node from an indexed mesh, one polygon per face.
+    pub fn from_mesh(vertices: &[Point3d], faces: &[Vec<usize>], color: Color) -> Self {
+        Polyhedron::new(
+            faces.iter().map(|face| {
+                Polygon::new(
+                    face.iter().map(|&i| vertices[i]).collect(),
+                    color,
+                    Transform::identity(),
+                )
+            }).collect()
+        )
+    }
+
+    /// Constructs an empty node holding `children`.
+    pub fn group(children: Vec<Polyhedron>) -> Self {
+        Polyhedron {
+            children,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_animation(mut self, animation: Animation) -> Self {
+        self.animation = Some(animation);
+        self
+    }
+
+    pub fn with_child(mut self, child: Polyhedron) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_point_cloud(mut self, cloud: PointCloud) -> Self {
+        self.point_clouds.push(cloud);
+        self
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    pub fn children(&self) -> &[Polyhedron] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Polyhedron> {
+        &mut self.children
+    }
+
+    /// Every polygon of this node and its descendants.
+    pub fn polygons(&self) -> Vec<&Polygon> {
+        let mut polygons: Vec<&Polygon> = self.polygons.iter().collect();
+        for child in &self.children {
+            polygons.append(&mut child.polygons());
+        }
+        polygons
+    }
+
+    /// Every point cloud of this node and its descendants.
+    pub fn point_clouds(&self) -> Vec<&PointCloud> {
+        let mut clouds: Vec<&PointCloud> = self.point_clouds.iter().collect();
+        for child in &self.children {
+            clouds.append(&mut child.point_clouds());
+        }
+        clouds
+    }
+
+    pub fn as_datasets(&self) -> Vec<Dataset<'_>> {
+        self.polygons().into_iter().map(|p| p.as_dataset()).collect()
+    }
+
+    /// Smallest and largest corner of the box around the node and its descendants,
+    /// placed at `parent` and without animation; `None` when there is nothing to draw.
+    pub fn bounds(&self, parent: &Transform) -> Option<(Point3d, Point3d)> {
+        let global = parent.compose(&self.transform);
+        let mut points: Points3d = Vec::new();
+        for polygon in &self.polygons {
+            let local = global.compose(&polygon.transform);
+            points.extend(polygon.vertices.iter().map(|a| local.apply(*a)));
+        }
+        for cloud in &self.point_clouds {
+            points.extend(cloud.points().iter().map(|a| global.apply(*a)));
+        }
+        for child in &self.children {
+            if let Some((low, high)) = child.bounds(&global) {
+                points.push(low);
+                points.push(high);
+            }
+        }
+        let first = *points.first()?;
+        Some(points.iter().fold((first, first), |(low, high), a| (
+            (low.0.min(a.0), low.1.min(a.1), low.2.min(a.2)),
+            (high.0.max(a.0), high.1.max(a.1), high.2.max(a.2)),
+        )))
+    }
+
+    /// Renders the node and its descendants, `parent` being the global transform of the parent node.
+    pub fn render(&mut self, world: &WorldMetrics, parent: &Transform) {
+        let global = match &self.animation {
+            Some(animation) => parent.compose(&self.transform.compose(&animation.at(world.frame_timestamp))),
+            None => parent.compose(&self.transform),
+        };
+        for polygon in self.polygons.iter_mut() {
+            polygon.render(world, &global);
+        }
+        for cloud in self.point_clouds.iter_mut() {
+            cloud.render(world, &global);
+        }
+        for child in self.children.iter_mut() {
+            child.render(world, &global);
+        }
+    }
+}
+
+
+/// Moves a point of a node placed at `global` into world space.
+pub fn to_world(a: Point3d, world: &WorldMetrics, global: &Transform) -> Point3d {
+    let (x, y, z) = global.apply(a);
+    (
+        x + world.world_translation_x,
+        y + world.world_translation_y,
+        z + world.world_translation_z,
+    )
+}
+
+/// Distance of a point in world space in front of the camera, along the direction it looks in.
+pub fn camera_depth(a: Point3d, world: &WorldMetrics) -> f64 {
+    dot(a, world.camera_direction())
+}
+
+/// Projects a point in world space onto the screen, as seen by the camera.
+pub fn project_to_screen(a: Point3d, world: &WorldMetrics) -> Point2d {
+    let ez = 1.0/((world.fov/2.0).tan());
+    project_point(a, (0.0, 0.0, ez), (world.camera_pitch,world.camera_yaw,0.0))
+}
+
+
+pub fn add((a0,a1,a2): Point3d, (b0,b1,b2): Point3d) -> Point3d {
+    (a0+b0, a1+b1, a2+b2)
+}
+
+pub fn sub((a0,a1,a2): Point3d, (b0,b1,b2): Point3d) -> Point3d {
+    (a0-b0, a1-b1, a2-b2)
+}
+
+pub fn scale((a0,a1,a2): Point3d, alpha: f64) -> Point3d {
+    (a0*alpha, a1*alpha, a2*alpha)
+}
+
+pub fn dot((a0,a1,a2): Point3d, (b0,b1,b2): Point3d) -> f64 {
+    (a0*b0) + (a1*b1) + (a2*b2)
+}
+
+pub fn cross((a0,a1,a2): Point3d, (b0,b1,b2): Point3d) -> Point3d {
+    ((a1*b2) - (a2*b1), (a2*b0) - (a0*b2), (a0*b1) - (a1*b0))
+}
+
+pub fn length(a: Point3d) -> f64 {
+    dot(a, a).sqrt()
+}
+
+pub fn normalize(a: Point3d) -> Point3d {
+    scale(a, 1.0/length(a))
+}
+
+
+pub fn project_point(a: Point3d, e: Point3d, t: Point3d) -> Point2d {
+    let (ax, ay, az): Point3d = a; // point to be projected
+    let (ex, ey, ez): Point3d = e; // display's surface position relative to the camera position <0,0,0>
+    let (tx, ty, tz): Point3d = t; // the angles of the camera (Tait-Brian angles)
+
+    let (cx, cy, cz): Point3d = (tx.cos(),ty.cos(),tz.cos()); //not to be confused with the position of the camera (ccx, ccy, ccz)
+    let (sx, sy, sz): Point3d = (tx.sin(),ty.sin(),tz.sin());
+
+    let (ccx, ccy, ccz): Point3d = (0.0,0.0,0.0); // add this as a parameter later if necessary. It's only added to the code for easy migration.
+
+    let x = ax-ccx;
+    let y = ay-ccy;
+    let z = az-ccz;
+
+    let dx = cy * ((sz*y) + (cz*x)) - (sy*z);
+    let dy = sx * ( (cy*z) + sy*( (sz*y) + (cz*x) ) ) + cx*( (cz*y) - (sz*x) );
+    let dz = cx * ( (cy*z) + sy*( (sz*y) + (cz*x) ) ) - sx*( (cz*y) - (sz*x) );
+
+    let bx = (ez/dz)*dx + ex;
+    let by = (ez/dz)*dy + ey;
+
+    (bx,by) // projected point
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+use tui::style::Color;
+use crate::app::AppResult;
+use crate::scene::{Point3d, Points3d, Polygon, Polyhedron, Transform};
+
+/// How the cells of a [`Surface`] are coloured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shading {
+    /// Every cell in the same colour.
+    Wireframe(Color),
+    /// Cells coloured from low to high, like a topographic map.
+    Height,
+}
+
+/// A grid of points, e.g. terrain.
+///
+/// `map[row][column]` holds the point at that grid position, or `None` for a hole.
+/// Cells are drawn between neighbouring points; cells touching a hole are left out,
+/// or drawn as a triangle when only one corner is missing.
+#[derive(Clone, Debug, Default)]
+pub struct Surface {
+    pub map: Vec<Vec<Option<Point3d>>>,
+}
+
+impl Surface {
+    pub fn new(map: Vec<Vec<Option<Point3d>>>) -> Self {
+        Surface { map }
+    }
+
+    /// Samples `height(x, z)` on a `width` by `depth` area centered on the origin, split into `resolution` cells along each side.
+    pub fn from_height_fn(width: f64, depth: f64, resolution: usize, height: impl Fn(f64, f64) -> Option<f64>) -> Self {
+        let resolution = resolution.max(1);
+        let map = (0..=resolution).map(|row| {
+            let z = depth * ((row as f64 / resolution as f64) - 0.5);
+            (0..=resolution).map(|column| {
+                let x = width * ((column as f64 / resolution as f64) - 0.5);
+                height(x, z).map(|y| (x, y, z))
+            }).collect()
+        }).collect();
+        Surface { map }
+    }
+
+    /// Lays out a heightmap with `cell_size` between samples, centered on the origin.
+    pub fn from_heights(heights: &[Vec<Option<f64>>], cell_size: f64, height_scale: f64) -> Self {
+        let rows = heights.len();
+        let columns = heights.iter().map(|r| r.len()).max().unwrap_or(0);
+        let x0 = cell_size * (columns.max(1) - 1) as f64 / 2.0;
+        let z0 = cell_size * (rows.max(1) - 1) as f64 / 2.0;
+        let map = heights.iter().enumerate().map(|(row, samples)| {
+            samples.iter().enumerate().map(|(column, sample)| {
+                sample.map(|h| (column as f64 * cell_size - x0, h * height_scale, row as f64 * cell_size - z0))
+            }).collect()
+        }).collect();
+        Surface { map }
+    }
+
+    /// Reads a heightmap from a CSV or PGM (`.pgm`) file.
+    pub fn load(path: impl AsRef<Path>, cell_size: f64, height_scale: f64) -> AppResult<Self> {
+        let path = path.as_ref();
+        let heights = match path.extension().and_then(|e| e.to_str()) {
+            Some("pgm") => parse_pgm(&fs::read(path)?)?,
+            _ => parse_csv(&fs::read_to_string(path)?)?,
+        };
+        Ok(Surface::from_heights(&heights, cell_size, height_scale))
+    }
+
+    /// Lowest and highest point of the surface.
+    pub fn height_range(&self) -> Option<(f64, f64)> {
+        self.points().fold(None, |range, (_, y, _)| match range {
+            None => Some((y, y)),
+            Some((low, high)) => Some((low.min(y), high.max(y))),
+        })
+    }
+
+    /// Builds the mesh of the surface, one polygon per cell.
+    pub fn to_polyhedron(&self, shading: Shading) -> Polyhedron {
+        let (low, high) = self.height_range().unwrap_or((0.0, 0.0));
+        let mut polygons: Vec<Polygon> = Vec::new();
+        for row in 1..self.map.len() {
+            for column in 1..self.map[row].len().min(self.map[row - 1].len()) {
+                let corners = [
+                    self.map[row - 1][column - 1],
+                    self.map[row - 1][column],
+                    self.map[row][column],
+                    self.map[row][column - 1],
+                ];
+                let cell: Points3d = corners.iter().flatten().cloned().collect();
+                if cell.len() < 3 {
+                    continue;
+                }
+                let color = match shading {
+                    Shading::Wireframe(color) => color,
+                    Shading::Height => {
+                        let y = cell.iter().map(|p| p.1).sum::<f64>() / cell.len() as f64;
+                        height_color(if high > low { (y - low) / (high - low) } else { 0.0 })
+                    }
+                };
+                polygons.push(Polygon::new(cell, color, Transform::identity()));
+            }
+        }
+        Polyhedron::new(polygons)
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point3d> + '_ {
+        self.map.iter().flatten().flatten().cloned()
+    }
+}
+
+/// Colour of a height from 0 (lowest) to 1 (highest): water, grass, rock, snow.
+pub fn height_color(t: f64) -> Color {
+    const STOPS: [(f64, (f64, f64, f64)); 5] = [
+        (0.0,  ( 30.0,  60.0, 170.0)),
+        (0.3,  ( 40.0, 150.0,  60.0)),
+        (0.6,  (120.0, 100.0,  60.0)),
+        (0.85, (150.0, 150.0, 150.0)),
+        (1.0,  (250.0, 250.0, 250.0)),
+    ];
+    let t = t.clamp(0.0, 1.0);
+    let i = STOPS.iter().rposition(|(at, _)| *at <= t).unwrap_or(0).min(STOPS.len() - 2);
+    let (t0, (r0, g0, b0)) = STOPS[i];
+    let (t1, (r1, g1, b1)) = STOPS[i + 1];
+    let f = ((t - t0) / (t1 - t0)).clamp(0.0, 1.0);
+    let mix = |a: f64, b: f64| (a + ((b - a) * f)).round() as u8;
+    Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
+
+/// One row of heights per line, separated by commas.
+/// Empty cells and non-numbers such as `nan` or `-` are holes.
+fn parse_csv(text: &str) -> AppResult<Vec<Vec<Option<f64>>>> {
+    let rows: Vec<Vec<Option<f64>>> = text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split(',')
+                .map(|cell| cell.trim().parse::<f64>().ok().filter(|h| h.is_finite()))
+                .collect()
+        })
+        .collect();
+    if rows.is_empty() {
+        return Err("heightmap has no rows".into());
+    }
+    Ok(rows)
+}
+
+/// Greyscale PGM image, plain (`P2`) or raw (`P5`), scaled to heights from 0 to 1.
+fn parse_pgm(bytes: &[u8]) -> AppResult<Vec<Vec<Option<f64>>>> {
+    // the header is four whitespace separated fields, with comments running from '#' to the end of the line
+    let mut fields: Vec<String> = Vec::new();
+    let mut i = 0;
+    while fields.len() < 4 {
+        match bytes.get(i) {
+            None => return Err("PGM header is truncated".into()),
+            Some(b'#') => while i < bytes.len() && bytes[i] != b'\n' { i += 1 },
+            Some(c) if c.is_ascii_whitespace() => i += 1,
+            Some(_) => {
+                let start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() { i += 1 }
+                fields.push(String::from_utf8_lossy(&bytes[start..i]).into_owned());
+            }
+        }
+    }
+    let number = |field: &str| -> AppResult<usize> {
+        field.parse().map_err(|_| format!("invalid PGM header field '{field}'").into())
+    };
+    let (width, height, max) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+    if max == 0 || max > u16::MAX as usize {
+        return Err(format!("invalid PGM maximum value {max}").into());
+    }
+    let count = width.checked_mul(height).ok_or_else(|| format!("PGM size {width}x{height} is too large"))?;
+
+    let samples: Vec<usize> = match fields[0].as_str() {
+        "P2" => String::from_utf8_lossy(&bytes[i..])
+            .split_ascii_whitespace()
+            .map(number)
+            .collect::<AppResult<_>>()?,
+        "P5" => {
+            let raster = &bytes[(i + 1).min(bytes.len())..];
+            if max < 256 {
+                raster.iter().map(|&b| b as usize).collect()
+            } else {
+                raster.chunks_exact(2).map(|b| ((b[0] as usize) << 8) | b[1] as usize).collect()
+            }
+        }
+        magic => return Err(format!("unsupported PGM format '{magic}'").into()),
+    };
+    if samples.len() < count {
+        return Err(format!("PGM has {} samples, expected {count}", samples.len()).into());
+    }
+
+    Ok(samples[..count]
+        .chunks(width.max(1))
+        .map(|row| row.iter().map(|&s| Some(s as f64 / max as f64)).collect())
+        .collect())
+}
@@ -0,0 +1,97 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use tui::style::Color;
+use crate::app::{App, AppResult, Shape};
+use crate::color::to_rgb;
+use crate::scene::Point2d;
+
+/// Size and look of exported images.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageOptions {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels; the screen from -1 to 1 fills it.
+    pub height: u32,
+    /// Width of the lines, in pixels.
+    pub line_width: f64,
+    /// Colour behind the scene, transparent when `None`.
+    pub background: Option<Color>,
+}
+
+impl ImageOptions {
+    /// Position in the image, in pixels from the top left, of a point on the screen.
+    pub fn to_pixels(&self, (x, y): Point2d) -> Point2d {
+        let (width, height) = (self.width as f64, self.height as f64);
+        ((width / 2.0) + (x * height / 2.0), (height / 2.0) * (1.0 - y))
+    }
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            width: 1200,
+            height: 800,
+            line_width: 1.5,
+            background: Some(Color::Black),
+        }
+    }
+}
+
+/// Writes the scene, as the camera of `app` sees it, to an SVG file.
+pub fn export(app: &mut App, path: impl AsRef<Path>, options: &ImageOptions) -> AppResult<()> {
+    let path = path.as_ref();
+    fs::write(path, to_svg(app, options)).map_err(|e| format!("{}: {e}", path.display()).into())
+}
+
+/// Draws the scene, as the camera of `app` sees it, as an SVG document.
+///
+/// Polygons become outlines, or filled shapes when not drawing wireframes, and point clouds become dots.
+pub fn to_svg(app: &mut App, options: &ImageOptions) -> String {
+    app.project_scene();
+    let to_pixels = |p| options.to_pixels(p);
+    let points = |points: &[Point2d]| {
+        points.iter().map(|p| {
+            let (x, y) = to_pixels(*p);
+            format!("{x:.2},{y:.2}")
+        }).collect::<Vec<_>>().join(" ")
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        options.width, options.height, options.width, options.height);
+    if let Some(background) = options.background {
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(background));
+    }
+    let _ = writeln!(svg, r#"<g stroke-width="{}" stroke-linejoin="round" stroke-linecap="round">"#, options.line_width);
+    for shape in app.shapes() {
+        match shape {
+            Shape::Polygon(p) => {
+                let color = hex(p.color());
+                // the vertices, unless some are behind the camera
+                let outline = if p.corners().is_empty() { p.projection() } else { p.corners() };
+                if p.is_closed() && !p.corners().is_empty() {
+                    let fill = if app.wireframe { "none" } else { &color };
+                    let _ = writeln!(svg, r#"<polygon points="{}" fill="{fill}" stroke="{color}"/>"#, points(outline));
+                } else {
+                    let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{color}"/>"#, points(outline));
+                }
+            }
+            Shape::Points(color, cloud) => {
+                let _ = writeln!(svg, r#"<g fill="{}">"#, hex(color));
+                for p in cloud {
+                    let (x, y) = to_pixels(*p);
+                    let _ = writeln!(svg, r#"<circle cx="{x:.2}" cy="{y:.2}" r="{}"/>"#, options.line_width);
+                }
+                let _ = writeln!(svg, "</g>");
+            }
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b) = to_rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
@@ -1,9 +1,15 @@
 use crate::app::{App, AppResult};
+use crate::cast::Recorder;
 use crate::event::EventHandler;
+use crossterm::cursor;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
-use std::io;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::panic;
+use std::path::Path;
 use tui::backend::Backend;
+use tui::layout::Rect;
 use tui::Terminal;
 
 /// Representation of a terminal user interface.
@@ -16,18 +22,27 @@ pub struct Tui<B: Backend> {
     terminal: Terminal<B>,
     /// Terminal event handler.
     pub events: EventHandler,
+    /// Recording of the drawn frames, if any.
+    recorder: Option<Recorder<BufWriter<File>>>,
 }
 
 impl<B: Backend> Tui<B> {
     /// Constructs a new instance of [`Tui`].
     pub fn new(terminal: Terminal<B>, events: EventHandler) -> Self {
-        Self { terminal, events }
+        Self { terminal, events, recorder: None }
     }
 
     /// Initializes the terminal interface.
     ///
     /// It enables the raw mode and sets terminal properties.
+    ///
+    /// A panic restores the terminal before the panic message is printed.
     pub fn init(&mut self) -> AppResult<()> {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            hook(info);
+        }));
         terminal::enable_raw_mode()?;
         crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
         self.terminal.hide_cursor()?;
@@ -35,22 +50,48 @@ impl<B: Backend> Tui<B> {
         Ok(())
     }
 
+    /// Records every frame drawn from now on to an asciinema `.cast` file.
+    pub fn record(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        // the size drawn at, which a replay fixes whatever the size of the terminal
+        let size = self.terminal.get_frame().size();
+        self.recorder = Some(Recorder::create(path, size.width, size.height)?);
+        Ok(())
+    }
+
     /// [`Draw`] the terminal interface by [`rendering`] the widgets.
     ///
     /// [`Draw`]: tui::Terminal::draw
     /// [`rendering`]: crate::app::App::render
     pub fn draw(&mut self, app: &mut App) -> AppResult<()> {
-        self.terminal.draw( |frame| app.render(frame))?;
+        let frame = self.terminal.draw( |frame| app.render(frame))?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(frame.buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Fits the terminal's buffers to its new size of `width` by `height` cells, clearing the screen.
+    pub fn resize(&mut self, width: u16, height: u16) -> AppResult<()> {
+        self.terminal.resize(Rect::new(0, 0, width, height))?;
         Ok(())
     }
 
     /// Exits the terminal interface.
     ///
-    /// It disables the raw mode and reverts back the terminal properties.
+    /// It disables the raw mode, reverts back the terminal properties and stops the event handler.
     pub fn exit(&mut self) -> AppResult<()> {
-        terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture)?;
+        restore()?;
         self.terminal.show_cursor()?;
+        self.events.stop();
         Ok(())
     }
 }
+
+/// Reverts the terminal properties set by [`Tui::init`].
+///
+/// It needs no [`Tui`], so it also works from a panic hook.
+pub fn restore() -> AppResult<()> {
+    terminal::disable_raw_mode()?;
+    crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture, cursor::Show)?;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! What the camera draws as it turns, whichever way it looks, and on terminals of different shapes.
+
+use std::f64::consts::PI;
+use tui::style::Color;
+use dddragon::app::{App, CameraMode, Viewport};
+use dddragon::headless;
+use dddragon::primitives;
+use dddragon::quaternions::Quaternion;
+use dddragon::scene::Transform;
+
+/// An app showing a cube in front of the camera.
+fn cube_app() -> App {
+    let mut app = App::new();
+    app.scene = primitives::cube(2.0, Color::Cyan).with_transform(Transform::new((0.0, 0.0, 5.0), Quaternion::new(1.0, 0.0, 0.0, 0.0)));
+    app
+}
+
+#[test]
+fn orbiting_camera_sees_the_model_from_every_side() {
+    for yaw in [0.0, PI / 2.0, 0.75 * PI, PI, 1.5 * PI] {
+        let mut app = cube_app();
+        app.frame_scene();
+        app.set_camera_mode(CameraMode::Orbit);
+        app.orbit(0.3, yaw, 1.0);
+        app.project_scene();
+        assert_eq!(app.shapes().len(), 6, "yaw {yaw}");
+    }
+}
+
+#[test]
+fn nothing_behind_the_camera_is_drawn() {
+    for (pitch, yaw) in [(0.0, PI), (0.0, PI / 2.0 + 0.5), (0.5, -PI / 2.0 - 0.5)] {
+        let mut app = cube_app();
+        app.world.camera_pitch = pitch;
+        app.world.camera_yaw = yaw;
+        app.project_scene();
+        assert!(app.shapes().is_empty(), "pitch {pitch}, yaw {yaw}");
+        for polygon in app.scene.polygons() {
+            assert!(polygon.projection().is_empty() && polygon.corners().is_empty(), "pitch {pitch}, yaw {yaw}");
+        }
+    }
+}
+
+#[test]
+fn changing_the_character_ratio_redraws_at_the_new_aspect() {
+    let mut app = cube_app();
+    app.character_ratio = 2.0;
+    let tall = headless::render_lines(&mut app, 40, 12).unwrap();
+    assert_eq!(app.viewport, Viewport::new(40, 12, 2.0));
+
+    app.character_ratio = 1.0;
+    let square = headless::render_lines(&mut app, 40, 12).unwrap();
+    assert_eq!(app.viewport, Viewport::new(40, 12, 1.0));
+    assert_ne!(tall, square);
+}
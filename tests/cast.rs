@@ -0,0 +1,75 @@
+//! Recording frames as an asciinema cast, written to memory here.
+
+use serde_json::Value;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use dddragon::cast::Recorder;
+
+fn buffer(width: u16, height: u16, text: &str) -> Buffer {
+    let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+    buffer.set_string(0, 0, text, Style::default());
+    buffer
+}
+
+/// The lines of a recording: the header, then an event per line.
+fn lines(cast: &[u8]) -> Vec<Value> {
+    String::from_utf8(cast.to_vec()).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+/// The code and data of an event line, checking its time.
+fn event(line: &Value) -> (&str, &str) {
+    let [time, code, data] = line.as_array().unwrap().as_slice() else { panic!("{line} is not an event") };
+    assert!(time.as_f64().unwrap() >= 0.0, "{line}");
+    (code.as_str().unwrap(), data.as_str().unwrap())
+}
+
+#[test]
+fn header_describes_the_terminal() {
+    let mut cast = Vec::new();
+    Recorder::new(&mut cast, 80, 24).unwrap();
+    let lines = lines(&cast);
+    assert_eq!(lines.len(), 1);
+    let header = &lines[0];
+    assert_eq!((&header["version"], &header["width"], &header["height"]), (&Value::from(2), &Value::from(80), &Value::from(24)));
+    assert!(header["timestamp"].is_u64() && header["env"]["TERM"].is_string(), "{header}");
+}
+
+#[test]
+fn frames_draw_only_what_changed() {
+    let mut cast = Vec::new();
+    let mut recorder = Recorder::new(&mut cast, 10, 2).unwrap();
+    recorder.frame(&buffer(10, 2, "dragon")).unwrap();
+    recorder.frame(&buffer(10, 2, "dragon")).unwrap();
+    recorder.frame(&buffer(10, 2, "dragen")).unwrap();
+    drop(recorder);
+
+    let lines = lines(&cast);
+    assert_eq!(lines.len(), 4);
+    let (code, first) = event(&lines[1]);
+    assert_eq!(code, "o");
+    assert!(first.starts_with("\x1b[?25l\x1b[2J"), "the first frame clears the screen: {first:?}");
+    assert!(first.contains("dragon"), "{first:?}");
+
+    let (_, same) = event(&lines[2]);
+    assert!(!same.contains('d') && !same.contains("\x1b[2J"), "an unchanged frame draws nothing: {same:?}");
+
+    let (_, changed) = event(&lines[3]);
+    assert!(changed.contains('e') && !changed.contains("drag"), "only the changed cells are drawn: {changed:?}");
+}
+
+#[test]
+fn resized_frames_are_drawn_in_full() {
+    let mut cast = Vec::new();
+    let mut recorder = Recorder::new(&mut cast, 10, 2).unwrap();
+    recorder.frame(&buffer(10, 2, "dragon")).unwrap();
+    recorder.frame(&buffer(12, 3, "dragon")).unwrap();
+    drop(recorder);
+
+    let lines = lines(&cast);
+    assert_eq!(lines.len(), 4);
+    assert_eq!(event(&lines[2]), ("r", "12x3"));
+    let (code, redrawn) = event(&lines[3]);
+    assert_eq!(code, "o");
+    assert!(redrawn.starts_with("\x1b[?25l\x1b[2J") && redrawn.contains("dragon"), "{redrawn:?}");
+}
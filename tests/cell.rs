@@ -0,0 +1,39 @@
+//! Working out the shape of terminal cells from the terminal's replies.
+
+use dddragon::cell::parse_replies;
+
+/// The answer to the device attributes query that follows the size queries.
+const ATTRIBUTES: &str = "\x1b[?62;22c";
+
+#[test]
+fn replies() {
+    for (replies, size, expected) in [
+        // the size of a cell
+        ("\x1b[6;20;10t", (80, 24), Some(2.0)),
+        ("\x1b[6;18;8t", (0, 0), Some(2.25)),
+        // the size of the text area, divided among the cells
+        ("\x1b[4;480;800t", (80, 24), Some(2.0)),
+        ("\x1b[4;480;800t", (0, 24), None),
+        // the cell size is preferred, whatever the order
+        ("\x1b[4;480;800t\x1b[6;16;8t", (80, 24), Some(2.0)),
+        ("\x1b[6;16;8t\x1b[4;100;100t", (80, 24), Some(2.0)),
+        // mixed with the device attributes
+        ("\x1b[6;20;10t\x1b[4;480;800t\x1b[?62;22c", (80, 24), Some(2.0)),
+        ("\x1b[?62;22c\x1b[4;480;800t", (80, 24), Some(2.0)),
+        // a terminal that answers only the device attributes
+        ("\x1b[?62;22c", (80, 24), None),
+        // cells of no size, or too odd a shape
+        ("\x1b[6;0;0t", (80, 24), None),
+        ("\x1b[6;100;1t", (80, 24), None),
+        // garbage
+        ("", (80, 24), None),
+        ("hello", (80, 24), None),
+        ("\x1b[6;x;10t", (80, 24), None),
+        ("\x1b[6;20t\x1b[6;20;10;5t", (80, 24), None),
+        ("\x1b[\x1b[;;t\x1b[99999999999;1;1t", (80, 24), None),
+    ] {
+        assert_eq!(parse_replies(replies, size), expected, "{replies:?} for {size:?}");
+        // the device attributes can come anywhere without changing the result
+        assert_eq!(parse_replies(&format!("{replies}{ATTRIBUTES}"), size), expected, "{replies:?} for {size:?}");
+    }
+}
@@ -0,0 +1,116 @@
+//! Clocks, and the simulation time played back from them.
+
+use dddragon::app::App;
+use dddragon::clock::{Clock, FixedStep, ManualClock, Playback, TIME_SCALES};
+use dddragon::headless;
+
+#[test]
+fn fixed_step() {
+    let mut clock = FixedStep::new(100.0, 40.0);
+    assert_eq!([clock.now(), clock.now(), clock.now()], [100.0, 140.0, 180.0]);
+
+    let mut still = FixedStep::new(5.0, 0.0);
+    assert_eq!([still.now(), still.now()], [5.0, 5.0]);
+}
+
+#[test]
+fn manual_clock_clones_share_the_time() {
+    let clock = ManualClock::new(10.0);
+    let mut given = clock.clone();
+    assert_eq!(given.now(), 10.0);
+    assert_eq!(given.now(), 10.0, "the time only moves when told to");
+
+    clock.advance(25.0);
+    assert_eq!(given.now(), 35.0);
+    clock.set(3.0);
+    assert_eq!(given.now(), 3.0);
+}
+
+#[test]
+fn frames_are_drawn_at_the_clock_time() {
+    let clock = ManualClock::new(1000.0);
+    let mut app = App::new();
+    app.clock = Box::new(clock.clone());
+    headless::render(&mut app, 40, 12).unwrap();
+    assert_eq!(app.world.frame_timestamp, 1000.0);
+
+    clock.advance(250.0);
+    headless::render(&mut app, 40, 12).unwrap();
+    assert_eq!(app.world.frame_timestamp, 1250.0);
+    headless::render(&mut app, 40, 12).unwrap();
+    assert_eq!(app.world.frame_timestamp, 1250.0);
+}
+
+#[test]
+fn playback_follows_the_clock_from_its_first_frame() {
+    let mut playback = Playback::default();
+    assert_eq!(playback.advance(500.0), 500.0);
+    assert_eq!(playback.advance(600.0), 600.0);
+    assert_eq!(playback.status(), None);
+}
+
+#[test]
+fn paused_playback_stands_still() {
+    let mut playback = Playback::default();
+    playback.advance(0.0);
+    playback.toggle_pause();
+    assert_eq!(playback.advance(100.0), 0.0);
+    assert_eq!(playback.status(), Some("paused".to_string()));
+
+    // the time paused for is skipped rather than caught up on
+    playback.toggle_pause();
+    assert_eq!(playback.advance(150.0), 50.0);
+}
+
+#[test]
+fn stepping_moves_one_frame_and_stays_paused() {
+    let mut playback = Playback::default();
+    playback.advance(0.0);
+    playback.step();
+    assert!(playback.paused);
+    assert_eq!(playback.advance(40.0), 40.0);
+    assert_eq!(playback.advance(80.0), 40.0);
+
+    playback.step();
+    assert_eq!(playback.advance(100.0), 60.0);
+    assert!(playback.paused);
+}
+
+#[test]
+fn speed_scales_the_time() {
+    let mut playback = Playback::default();
+    playback.advance(0.0);
+    playback.faster();
+    assert_eq!(playback.scale(), 2.0);
+    assert_eq!(playback.advance(100.0), 200.0);
+    assert_eq!(playback.status(), Some("2x".to_string()));
+
+    playback.toggle_pause();
+    assert_eq!(playback.status(), Some("paused 2x".to_string()));
+}
+
+#[test]
+fn speed_is_kept_to_the_scales() {
+    let mut playback = Playback::default();
+    for _ in 0..TIME_SCALES.len() + 2 {
+        playback.faster();
+    }
+    assert_eq!(playback.scale(), 10.0);
+    for _ in 0..TIME_SCALES.len() + 2 {
+        playback.slower();
+    }
+    assert_eq!(playback.scale(), 0.1);
+
+    for (scale, expected) in [(3.0, 3.0), (1000.0, 10.0), (0.0, 0.1), (-2.0, 0.1), (f64::INFINITY, 10.0), (f64::NAN, 10.0)] {
+        playback.set_scale(scale);
+        assert_eq!(playback.scale(), expected, "{scale}");
+    }
+
+    // from between two scales, the next ones up and down
+    playback.set_scale(3.0);
+    playback.faster();
+    assert_eq!(playback.scale(), 5.0);
+    playback.set_scale(3.0);
+    playback.slower();
+    assert_eq!(playback.scale(), 2.0);
+}
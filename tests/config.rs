@@ -0,0 +1,67 @@
+//! Display settings from the config file and the command line.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use clap::Parser;
+use dddragon::app::App;
+use dddragon::cli::Args;
+use dddragon::config::{Config, Display};
+
+/// A path in the temporary directory that no other test uses.
+fn temporary(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("dddragon-{}-{name}", std::process::id()))
+}
+
+fn args(arguments: &[&str]) -> Args {
+    Args::try_parse_from(["dddragon"].iter().chain(arguments)).unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[test]
+fn invalid_display_settings() {
+    let default = Display::default();
+    for (display, error) in [
+        (Display { tick_rate: 0, ..default.clone() }, "tick_rate must be at least 1 millisecond"),
+        (Display { line_points: 0, ..default.clone() }, "line_points must be between 1 and 100000, not 0"),
+        (Display { line_points: 100_001, ..default.clone() }, "line_points must be between 1 and 100000, not 100001"),
+        (Display { fov: f64::NAN, ..default.clone() }, "fov must be between 0 and 180 degrees, not NaN"),
+        (Display { fov: 0.0, ..default.clone() }, "fov must be between 0 and 180 degrees, not 0"),
+        (Display { fov: 180.0, ..default.clone() }, "fov must be between 0 and 180 degrees, not 180"),
+        (Display { fov: -30.0, ..default.clone() }, "fov must be between 0 and 180 degrees, not -30"),
+        (Display { character_ratio: 0.0, ..default.clone() }, "character_ratio must be more than 0, not 0"),
+        (Display { near_plane: f64::INFINITY, ..default.clone() }, "near_plane must be more than 0, not inf"),
+    ] {
+        assert_eq!(display.validate(str::to_string), Err(error.to_string()));
+    }
+    assert_eq!(default.validate(str::to_string), Ok(()));
+}
+
+#[test]
+fn config_file_errors_name_the_file_and_setting() {
+    let path = temporary("invalid.toml");
+    fs::write(&path, "[display]\nfov = 200\n").unwrap();
+    let error = Config::load(&path).unwrap_err().to_string();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(error, format!("{}: display.fov must be between 0 and 180 degrees, not 200", path.display()));
+}
+
+#[test]
+fn command_line_overrides_the_config_file() {
+    let path = temporary("config.toml");
+    fs::write(&path, "[display]\nfov = 60\nline_points = 50\n").unwrap();
+    let config_path = path.to_str().unwrap();
+    let from_file = args(&["--config", config_path]).config();
+    let overridden = args(&["--config", config_path, "--fov", "30"]).config();
+    let invalid = args(&["--config", config_path, "--line-points", "0"]).config();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(from_file.unwrap().display.fov, 60.0);
+    let config = overridden.unwrap();
+    assert_eq!((config.display.fov, config.display.line_points), (30.0, 50));
+    assert_eq!(invalid.unwrap_err().to_string(), "--line-points must be between 1 and 100000, not 0");
+
+    let mut app = App::new();
+    args(&[]).apply(&mut app, &config).unwrap();
+    assert_eq!(app.world.fov, 30f64.to_radians());
+    assert_eq!(app.world.line_points, 50);
+}
@@ -0,0 +1,36 @@
+//! Exporting frames from the keyboard.
+//!
+//! Frames are written to the working directory, so this is one test in its own binary.
+
+use std::env;
+use std::fs;
+use dddragon::app::App;
+use dddragon::handler::perform;
+use dddragon::keymap::Action;
+
+#[test]
+fn exports_report_their_file_and_do_not_overwrite() {
+    let directory = env::temp_dir().join(format!("dddragon-{}-exports", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    env::set_current_dir(&directory).unwrap();
+
+    let mut app = App::new();
+    app.world.frame_timestamp = 1000.0;
+    for (action, extension) in [(Action::ExportSvg, "svg"), (Action::ExportPng, "png")] {
+        perform(action, &mut app).unwrap();
+        assert_eq!(app.message, Some(Ok(format!("exported frame-1000.{extension}"))));
+        perform(action, &mut app).unwrap();
+        assert_eq!(app.message, Some(Ok(format!("exported frame-1000-2.{extension}"))));
+        assert!(directory.join(format!("frame-1000.{extension}")).exists());
+        assert!(directory.join(format!("frame-1000-2.{extension}")).exists());
+    }
+
+    // with the directory gone the export fails, without quitting
+    fs::remove_dir_all(&directory).unwrap();
+    for (action, extension) in [(Action::ExportSvg, "svg"), (Action::ExportPng, "png")] {
+        perform(action, &mut app).unwrap();
+        assert!(app.running);
+        let prefix = format!("could not export frame-1000.{extension}: ");
+        assert!(matches!(&app.message, Some(Err(error)) if error.starts_with(&prefix)), "{:?}", app.message);
+    }
+}
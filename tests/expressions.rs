@@ -0,0 +1,89 @@
+//! Parsing and evaluating the arithmetic expressions of plots.
+
+use dddragon::plot::Expression;
+
+fn eval(source: &str) -> f64 {
+    Expression::parse(source, &["x", "y"])
+        .unwrap_or_else(|e| panic!("{source}: {e}"))
+        .eval(&[3.0, 4.0])
+}
+
+fn error(source: &str) -> String {
+    match Expression::parse(source, &["x", "y"]) {
+        Ok(expression) => panic!("{source} parsed as {expression:?}"),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn precedence() {
+    for (source, expected) in [
+        ("1 + 2 * 3", 7.0),
+        ("(1 + 2) * 3", 9.0),
+        ("10 - 4 - 3", 3.0),
+        ("12 / 3 / 2", 2.0),
+        ("2 * 3 ^ 2", 18.0),
+        ("x * x + y * y", 25.0),
+        ("1 - -1", 2.0),
+    ] {
+        assert_eq!(eval(source), expected, "{source}");
+    }
+}
+
+#[test]
+fn powers_are_right_associative() {
+    assert_eq!(eval("2^3^2"), 512.0);
+    assert_eq!(eval("2^-1"), 0.5);
+}
+
+#[test]
+fn unary_minus_is_below_powers() {
+    assert_eq!(eval("-2^2"), -4.0);
+    assert_eq!(eval("(-2)^2"), 4.0);
+    assert_eq!(eval("--x"), 3.0);
+}
+
+#[test]
+fn numbers() {
+    for (source, expected) in [
+        ("1e3", 1000.0),
+        ("1e-3", 0.001),
+        ("2.5e+2", 250.0),
+        (".5", 0.5),
+        ("e", std::f64::consts::E),
+    ] {
+        assert_eq!(eval(source), expected, "{source}");
+    }
+    assert_eq!(error("1.2.3"), "invalid number '1.2.3'");
+    // without a digit after it, the e is the constant, and there is no implicit multiplication
+    assert_eq!(error("2e"), "unexpected 'e' in expression");
+}
+
+#[test]
+fn functions() {
+    assert_eq!(eval("min(x, y)"), 3.0);
+    assert_eq!(eval("max(x, y)"), 4.0);
+    assert_eq!(eval("sqrt(x^2 + y^2)"), 5.0);
+    assert_eq!(eval("atan2(0, 1)"), 0.0);
+}
+
+#[test]
+fn wrong_argument_counts() {
+    assert_eq!(error("min(1)"), "min takes 2 argument(s)");
+    assert_eq!(error("sin(1, 2)"), "sin takes 1 argument(s)");
+}
+
+#[test]
+fn errors() {
+    for (source, expected) in [
+        ("", "unexpected end of expression"),
+        ("1 +", "unexpected end of expression"),
+        ("(1 + 2", "expected ')' in expression"),
+        ("1 2", "unexpected '2' in expression"),
+        ("z", "unknown variable 'z'"),
+        ("foo(1)", "unknown function 'foo'"),
+        ("1 $ 2", "unexpected character '$' in expression"),
+    ] {
+        assert_eq!(error(source), expected, "{source}");
+    }
+}
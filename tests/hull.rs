@@ -0,0 +1,71 @@
+//! Convex hulls of point sets, whole and degenerate.
+
+use tui::style::Color;
+use dddragon::app::App;
+use dddragon::headless;
+use dddragon::hull::{convex_hull, convex_polyhedron};
+use dddragon::scene::{Point3d, Points3d};
+
+fn cube_corners() -> Points3d {
+    (0..8).map(|i| {
+        let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+        (sign(1), sign(2), sign(4))
+    }).collect()
+}
+
+/// The sizes of the faces, smallest first.
+fn face_sizes(faces: &[Vec<usize>]) -> Vec<usize> {
+    let mut sizes: Vec<usize> = faces.iter().map(Vec::len).collect();
+    sizes.sort_unstable();
+    sizes
+}
+
+#[test]
+fn cube() {
+    let mut points = cube_corners();
+    // the center, and points on a face and an edge, are not corners of the hull
+    points.extend([(0.0, 0.0, 0.0), (0.5, 0.5, 1.0), (1.0, 1.0, 0.0)]);
+    let faces = convex_hull(&points);
+    assert_eq!(face_sizes(&faces), [4; 6]);
+    assert!(faces.iter().flatten().all(|&i| i < 8));
+}
+
+#[test]
+fn dodecahedron() {
+    let phi = (1.0 + 5.0f64.sqrt()) / 2.0;
+    let mut points = cube_corners();
+    for (a, b) in [(1.0 / phi, phi), (-1.0 / phi, phi), (1.0 / phi, -phi), (-1.0 / phi, -phi)] {
+        points.extend([(0.0, a, b), (a, b, 0.0), (b, 0.0, a)]);
+    }
+    assert_eq!(face_sizes(&convex_hull(&points)), [5; 12]);
+}
+
+#[test]
+fn flat_points_give_one_face() {
+    let points: Points3d = vec![(0.0, 0.0, 0.0), (2.0, 0.0, 0.0), (2.0, 2.0, 0.0), (0.0, 2.0, 0.0), (1.0, 1.0, 0.0)];
+    assert_eq!(face_sizes(&convex_hull(&points)), [4]);
+}
+
+#[test]
+fn degenerate_points_give_no_faces() {
+    let cases: [(&str, Points3d); 6] = [
+        ("no points", vec![]),
+        ("one point", vec![(0.0, 0.0, 5.0)]),
+        ("the same point twice", vec![(1.0, 2.0, 3.0); 2]),
+        ("the same point many times", vec![(0.0, 0.0, 0.0); 10]),
+        ("two points", vec![(0.0, 0.0, 0.0), (1.0, 1.0, 1.0)]),
+        ("points on a line", (0..5).map(|i| (i as f64, 2.0 * i as f64, 0.0)).collect()),
+    ];
+    for (name, points) in cases {
+        assert_eq!(convex_hull(&points), Vec::<Vec<usize>>::new(), "{name}");
+        assert!(convex_polyhedron(&points, Color::White).polygons().is_empty(), "{name}");
+    }
+}
+
+#[test]
+fn degenerate_hull_renders() {
+    let point: Point3d = (0.0, 0.0, 5.0);
+    let mut app = App::new();
+    app.scene = convex_polyhedron(&[point], Color::White);
+    headless::render_lines(&mut app, 40, 12).unwrap();
+}
@@ -0,0 +1,223 @@
+//! Reading PLY and glTF models, from small files written out here.
+
+use serde_json::{json, Value};
+use tui::style::Color;
+use dddragon::gltf;
+use dddragon::ply::Ply;
+use dddragon::scene::{Point3d, Polyhedron, Transform};
+
+const GREY: (u8, u8, u8) = (128, 128, 128);
+
+/// The corners of a unit triangle and a unit square in the z = 0 plane.
+const TRIANGLE: [Point3d; 3] = [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)];
+const SQUARE: [Point3d; 4] = [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (1.0, 1.0, 0.0)];
+
+fn bounds(model: &Polyhedron) -> (Point3d, Point3d) {
+    model.bounds(&Transform::identity()).expect("the model has something to draw")
+}
+
+fn colors(model: &Polyhedron) -> Vec<Color> {
+    model.polygons().iter().map(|p| p.color()).collect()
+}
+
+fn ply_header(format: &str, colored: bool) -> String {
+    let color = if colored { "property uchar red\nproperty uchar green\nproperty uchar blue\n" } else { "" };
+    format!(
+        "ply\nformat {format} 1.0\ncomment a unit triangle\nelement vertex 3\n\
+         property float x\nproperty float y\nproperty float z\n{color}\
+         element face 1\nproperty list uchar int vertex_indices\nend_header\n"
+    )
+}
+
+/// The triangle in a binary PLY file, red, green and blue at the corners when `colored`.
+fn binary_triangle(big_endian: bool, colored: bool) -> Vec<u8> {
+    let format = if big_endian { "binary_big_endian" } else { "binary_little_endian" };
+    let mut bytes = ply_header(format, colored).into_bytes();
+    let rgb = [[255, 0, 0], [0, 255, 0], [0, 0, 255]];
+    for (i, (x, y, z)) in TRIANGLE.into_iter().enumerate() {
+        for v in [x as f32, y as f32, z as f32] {
+            bytes.extend(if big_endian { v.to_be_bytes() } else { v.to_le_bytes() });
+        }
+        if colored {
+            bytes.extend(rgb[i]);
+        }
+    }
+    bytes.push(3);
+    for i in [0i32, 1, 2] {
+        bytes.extend(if big_endian { i.to_be_bytes() } else { i.to_le_bytes() });
+    }
+    bytes
+}
+
+#[test]
+fn ascii_ply() {
+    let text = ply_header("ascii", false) + "0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+    let model = Ply::parse(text.as_bytes()).unwrap().to_polyhedron(GREY).unwrap();
+    assert_eq!(bounds(&model), ((0.0, 0.0, 0.0), (1.0, 1.0, 0.0)));
+    assert_eq!(colors(&model), [Color::Rgb(128, 128, 128)]);
+}
+
+#[test]
+fn ascii_ply_with_colors() {
+    let text = ply_header("ascii", true) + "0 0 0 255 0 0\n1 0 0 0 255 0\n0 1 0 0 0 255\n3 0 1 2\n";
+    let model = Ply::parse(text.as_bytes()).unwrap().to_polyhedron(GREY).unwrap();
+    assert_eq!(colors(&model), [Color::Rgb(85, 85, 85)]);
+}
+
+#[test]
+fn ply_colors_follow_their_declared_type() {
+    // dark integer colours stay dark, rather than being taken for unit floats
+    let text = ply_header("ascii", true) + "0 0 0 1 0 0\n1 0 0 1 0 0\n0 1 0 1 0 0\n3 0 1 2\n";
+    let model = Ply::parse(text.as_bytes()).unwrap().to_polyhedron(GREY).unwrap();
+    assert_eq!(colors(&model), [Color::Rgb(1, 0, 0)]);
+
+    let text = ply_header("ascii", false).replace("property float z\n", "property float z\nproperty float red\nproperty double green\nproperty float32 blue\n")
+        + "0 0 0 1 0.5 0\n1 0 0 1 0.5 0\n0 1 0 1 0.5 2\n3 0 1 2\n";
+    let ply = Ply::parse(text.as_bytes()).unwrap();
+    assert_eq!(ply.element("vertex").unwrap().colors(), Some(vec![(255, 128, 0), (255, 128, 0), (255, 128, 255)]));
+    assert_eq!(colors(&ply.to_polyhedron(GREY).unwrap()), [Color::Rgb(255, 128, 85)]);
+}
+
+#[test]
+fn binary_ply() {
+    for big_endian in [false, true] {
+        for colored in [false, true] {
+            let model = Ply::parse(&binary_triangle(big_endian, colored)).unwrap().to_polyhedron(GREY).unwrap();
+            assert_eq!(bounds(&model), ((0.0, 0.0, 0.0), (1.0, 1.0, 0.0)), "big endian {big_endian}, coloured {colored}");
+            let expected = if colored { Color::Rgb(85, 85, 85) } else { Color::Rgb(128, 128, 128) };
+            assert_eq!(colors(&model), [expected], "big endian {big_endian}, coloured {colored}");
+        }
+    }
+}
+
+#[test]
+fn truncated_ply() {
+    let bytes = binary_triangle(false, true);
+    let error = Ply::parse(&bytes[..bytes.len() - 2]).unwrap_err();
+    assert_eq!(error.to_string(), "PLY data is truncated");
+}
+
+#[test]
+fn ply_faces_must_refer_to_vertices() {
+    for (face, index) in [("3 0 1 3", "3"), ("3 0 -1 2", "-1"), ("3 0 1.5 2", "1.5")] {
+        let text = ply_header("ascii", false) + "0 0 0\n1 0 0\n0 1 0\n" + face + "\n";
+        let error = Ply::parse(text.as_bytes()).unwrap().to_polyhedron(GREY).unwrap_err();
+        assert_eq!(error.to_string(), format!("PLY face refers to missing vertex {index}"));
+    }
+}
+
+#[test]
+fn ply_without_faces_is_a_point_cloud() {
+    let text = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nend_header\n0 0 0\n1 2 3\n";
+    let model = Ply::parse(text.as_bytes()).unwrap().to_polyhedron(GREY).unwrap();
+    assert!(model.polygons().is_empty());
+    assert_eq!(model.point_clouds()[0].points(), &vec![(0.0, 0.0, 0.0), (1.0, 2.0, 3.0)]);
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | ((b as u32) << (16 - (8 * i))));
+        for i in 0..4 {
+            text.push(if i <= chunk.len() { DIGITS[((n >> (18 - (6 * i))) & 63) as usize] as char } else { '=' });
+        }
+    }
+    text
+}
+
+/// A glTF document with one mesh drawing `positions` in primitive `mode`, and the given `nodes`.
+///
+/// The buffer holds the positions, cut short by `missing` bytes.
+fn gltf(positions: &[Point3d], mode: u32, nodes: &str, missing: usize) -> String {
+    let mut buffer: Vec<u8> = positions.iter()
+        .flat_map(|&(x, y, z)| [x as f32, y as f32, z as f32])
+        .flat_map(f32::to_le_bytes)
+        .collect();
+    let length = buffer.len();
+    buffer.truncate(length - missing);
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "scenes": [{{ "nodes": [0] }}],
+            "nodes": {nodes},
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "mode": {mode}, "material": 0 }}] }}],
+            "materials": [{{ "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1] }} }}],
+            "accessors": [{{ "bufferView": 0, "componentType": 5126, "count": {count}, "type": "VEC3" }}],
+            "bufferViews": [{{ "buffer": 0, "byteLength": {length} }}],
+            "buffers": [{{ "byteLength": {length}, "uri": "data:application/octet-stream;base64,{data}" }}]
+        }}"#,
+        count = positions.len(),
+        data = base64(&buffer),
+    )
+}
+
+#[test]
+fn gltf_triangle() {
+    let model = gltf::parse(&gltf(&TRIANGLE, 4, r#"[{ "mesh": 0 }]"#, 0)).unwrap();
+    assert_eq!(bounds(&model), ((0.0, 0.0, 0.0), (1.0, 1.0, 0.0)));
+    assert_eq!(colors(&model), [Color::Rgb(255, 0, 0)]);
+}
+
+#[test]
+fn gltf_triangle_strip() {
+    let model = gltf::parse(&gltf(&SQUARE, 5, r#"[{ "mesh": 0 }]"#, 0)).unwrap();
+    assert_eq!(model.polygons().len(), 2);
+    assert!(model.polygons().iter().all(|p| p.is_closed()));
+    assert_eq!(bounds(&model), ((0.0, 0.0, 0.0), (1.0, 1.0, 0.0)));
+}
+
+#[test]
+fn gltf_node_matrix() {
+    // column major: scaled by 2 and moved 5 along x
+    let nodes = r#"[{ "mesh": 0, "matrix": [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 5, 0, 0, 1] }]"#;
+    let model = gltf::parse(&gltf(&TRIANGLE, 4, nodes, 0)).unwrap();
+    assert_eq!(bounds(&model), ((5.0, 0.0, 0.0), (7.0, 2.0, 0.0)));
+}
+
+#[test]
+fn gltf_scale_applies_to_children() {
+    let nodes = r#"[
+        { "scale": [2, 2, 2], "children": [1] },
+        { "mesh": 0, "translation": [1, 0, 0] }
+    ]"#;
+    let model = gltf::parse(&gltf(&TRIANGLE, 4, nodes, 0)).unwrap();
+    assert_eq!(bounds(&model), ((2.0, 0.0, 0.0), (4.0, 2.0, 0.0)));
+}
+
+#[test]
+fn truncated_gltf_buffer() {
+    let error = gltf::parse(&gltf(&TRIANGLE, 4, r#"[{ "mesh": 0 }]"#, 4)).unwrap_err();
+    assert_eq!(error.to_string(), "glTF accessor reads past the end of its buffer");
+}
+
+/// The error of the triangle document with its accessor and buffer view changed by `change`.
+fn gltf_error(change: impl Fn(&mut Value)) -> String {
+    let mut document: Value = serde_json::from_str(&gltf(&TRIANGLE, 4, r#"[{ "mesh": 0 }]"#, 0)).unwrap();
+    change(&mut document);
+    gltf::parse(&document.to_string()).unwrap_err().to_string()
+}
+
+#[test]
+fn malformed_gltf_accessors() {
+    let past_end = "glTF accessor reads past the end of its buffer";
+    assert_eq!(gltf_error(|d| d["accessors"][0]["count"] = json!(1_000_000_000_000_000_000u64)), past_end);
+    assert_eq!(gltf_error(|d| d["accessors"][0]["count"] = json!(u64::MAX)), past_end);
+    assert_eq!(gltf_error(|d| d["accessors"][0]["byteOffset"] = json!(u64::MAX)), past_end);
+    assert_eq!(gltf_error(|d| d["bufferViews"][0]["byteOffset"] = json!(u64::MAX)), past_end);
+    assert_eq!(gltf_error(|d| d["bufferViews"][0]["byteStride"] = json!(u64::MAX)), past_end);
+    assert_eq!(
+        gltf_error(|d| d["bufferViews"][0]["byteStride"] = json!(0)),
+        "glTF byte stride 0 is shorter than an element of 12 bytes",
+    );
+    assert_eq!(
+        gltf_error(|d| {
+            d["accessors"][0].as_object_mut().unwrap().remove("bufferView");
+        }),
+        "glTF accessor has no bufferView",
+    );
+    assert_eq!(
+        gltf_error(|d| d["accessors"][0]["sparse"] = json!({ "count": 1, "indices": {}, "values": {} })),
+        "sparse glTF accessors are not supported",
+    );
+}
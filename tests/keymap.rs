@@ -0,0 +1,95 @@
+//! Key bindings as written in the config file.
+
+use std::collections::HashMap;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use dddragon::config::Config;
+use dddragon::keymap::{Action, KeyBinding, Keymap, Sensitivity};
+
+fn key(text: &str) -> KeyBinding {
+    text.parse().unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[test]
+fn parsing() {
+    for (text, code, modifiers) in [
+        ("w", KeyCode::Char('w'), KeyModifiers::NONE),
+        ("+", KeyCode::Char('+'), KeyModifiers::NONE),
+        ("ctrl++", KeyCode::Char('+'), KeyModifiers::CONTROL),
+        ("ctrl+s", KeyCode::Char('s'), KeyModifiers::CONTROL),
+        ("Control+Alt+x", KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT),
+        ("shift+s", KeyCode::Char('S'), KeyModifiers::NONE),
+        ("S", KeyCode::Char('S'), KeyModifiers::NONE),
+        ("shift+up", KeyCode::Up, KeyModifiers::SHIFT),
+        ("space", KeyCode::Char(' '), KeyModifiers::NONE),
+        ("Esc", KeyCode::Esc, KeyModifiers::NONE),
+        ("f1", KeyCode::F(1), KeyModifiers::NONE),
+        ("f12", KeyCode::F(12), KeyModifiers::NONE),
+    ] {
+        assert_eq!(key(text), KeyBinding { code, modifiers }, "{text}");
+    }
+}
+
+#[test]
+fn parsing_errors() {
+    for (text, reason) in [
+        ("f13", "unknown key"),
+        ("f0", "unknown key"),
+        ("enterr", "unknown key"),
+        ("", "unknown key"),
+        ("hyper+x", "unknown modifier 'hyper', expected ctrl, alt or shift"),
+    ] {
+        assert_eq!(text.parse::<KeyBinding>(), Err(format!("invalid key '{text}': {reason}")), "{text}");
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    for text in ["w", "+", "ctrl++", "ctrl+s", "ctrl+alt+x", "S", "shift+up", "space", "esc", "enter", "backtab", "pagedown", "f12"] {
+        assert_eq!(key(text).to_string(), text);
+        assert_eq!(key(&key(text).to_string()), key(text), "{text}");
+    }
+    // shift with a character is written as the shifted character
+    assert_eq!(key("shift+s").to_string(), "S");
+}
+
+#[test]
+fn shift_is_folded_into_characters() {
+    let keymap = Keymap::default();
+    for modifiers in [KeyModifiers::CONTROL, KeyModifiers::CONTROL | KeyModifiers::SHIFT] {
+        let event = KeyEvent::new(KeyCode::Char('s'), modifiers);
+        let expected = (modifiers == KeyModifiers::CONTROL).then_some(Action::Save);
+        assert_eq!(keymap.action(event), expected, "{modifiers:?}");
+    }
+    assert_eq!(KeyBinding::from(KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT)), key("S"));
+}
+
+#[test]
+fn bindings_replace_defaults() {
+    let bindings = HashMap::from([(Action::Quit, vec![key("q")]), (Action::MoveBack, vec![key("esc")])]);
+    let keymap = Keymap::new(&bindings, Sensitivity::default()).unwrap();
+    assert_eq!(keymap.keys(Action::Quit), [key("q")]);
+    assert_eq!(keymap.keys(Action::MoveBack), [key("esc")]);
+    assert_eq!(keymap.keys(Action::MoveForward), [key("w")]);
+}
+
+#[test]
+fn a_key_is_bound_to_one_action() {
+    let bindings = HashMap::from([(Action::Quit, vec![key("x")]), (Action::Save, vec![key("x")])]);
+    let error = Keymap::new(&bindings, Sensitivity::default()).unwrap_err();
+    assert!(
+        error == "key 'x' is bound to both quit and save" || error == "key 'x' is bound to both save and quit",
+        "{error}",
+    );
+}
+
+#[test]
+fn config_bindings() {
+    let config: Config = toml::from_str("[keys.bindings]\nexport_png = [\"ctrl+e\", \"shift+i\"]\n").unwrap();
+    let keymap = config.keymap().unwrap();
+    let mut keys = keymap.keys(Action::ExportPng);
+    keys.sort_by_key(|k| k.to_string());
+    assert_eq!(keys, [key("I"), key("ctrl+e")]);
+
+    let error = toml::from_str::<Config>("[keys.bindings]\nquit = [\"f13\"]\n").unwrap_err();
+    assert!(error.to_string().contains("invalid key 'f13': unknown key"), "{error}");
+}
@@ -0,0 +1,110 @@
+//! Saving levels and loading them back.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use dddragon::app::App;
+use dddragon::handler::perform;
+use dddragon::keymap::Action;
+use dddragon::level::Level;
+
+/// A path in the temporary directory that no other test uses.
+fn temporary(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("dddragon-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn demo_level_round_trips() {
+    let path = temporary("demo.json");
+    let level = Level::demo();
+    level.save(&path).unwrap();
+    let loaded = Level::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), level);
+}
+
+#[test]
+fn saving_leaves_strings_alone() {
+    let path = temporary("strings.json");
+    let mut level = Level::demo();
+    level.objects[0].name = "wing[1,2]".into();
+    level.objects[1].name = "[0.5,\n -1]".into();
+    level.save(&path).unwrap();
+    let loaded = Level::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), level);
+}
+
+#[test]
+fn saving_reports_the_file() {
+    let path = temporary("saved.json");
+    let mut app = App::new();
+    app.level_path = Some(path.clone());
+    perform(Action::Save, &mut app).unwrap();
+    let loaded = Level::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), app.level);
+    assert_eq!(app.message, Some(Ok(format!("saved {}", path.display()))));
+}
+
+#[test]
+fn failed_save_keeps_running() {
+    let path = temporary("missing").join("level.json");
+    let mut app = App::new();
+    app.level_path = Some(path.clone());
+    perform(Action::Save, &mut app).unwrap();
+    assert!(app.running);
+    let error = app.message.unwrap().unwrap_err();
+    assert!(error.starts_with(&format!("could not save {}: ", path.display())), "{error}");
+}
+
+/// Writes `text` to `path` with a modification time `seconds` from now, as an editor saving it later would.
+fn edit(path: &Path, text: &str, seconds: u64) {
+    fs::write(path, text).unwrap();
+    let file = fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(seconds)).unwrap();
+}
+
+#[test]
+fn changed_level_file_is_reloaded() {
+    let path = temporary("reloaded.json");
+    Level::demo().save(&path).unwrap();
+    let mut app = App::new();
+    app.open(&path).unwrap();
+    app.tick();
+    assert_eq!(app.level, Level::demo());
+
+    let mut level = Level::demo();
+    level.objects.truncate(1);
+    edit(&path, &serde_json::to_string(&level).unwrap(), 60);
+    app.tick();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(app.level, level);
+    assert_eq!(app.reload_error, None);
+}
+
+#[test]
+fn invalid_level_file_keeps_the_old_level() {
+    let path = temporary("invalid.json");
+    Level::demo().save(&path).unwrap();
+    let mut app = App::new();
+    app.open(&path).unwrap();
+    let polygons = app.scene.polygons().len();
+
+    edit(&path, "{ \"objects\": [", 60);
+    app.tick();
+    assert_eq!(app.level, Level::demo());
+    assert_eq!(app.scene.polygons().len(), polygons);
+    let error = app.reload_error.clone().expect("the error is shown");
+    assert!(error.starts_with(&format!("{}: ", path.display())), "{error}");
+
+    // fixing the file clears the error
+    let mut level = Level::demo();
+    level.objects.truncate(1);
+    edit(&path, &serde_json::to_string(&level).unwrap(), 120);
+    app.tick();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(app.level, level);
+    assert_eq!(app.reload_error, None);
+}
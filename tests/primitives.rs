@@ -0,0 +1,117 @@
+//! The procedural meshes: their sizes, and which way their faces turn.
+
+use std::collections::HashSet;
+use tui::style::Color;
+use dddragon::primitives;
+use dddragon::scene::{add, cross, dot, length, normalize, scale, sub, Point3d, Polyhedron};
+
+/// Number of distinct vertices and of faces.
+fn counts(mesh: &Polyhedron) -> (usize, usize) {
+    let polygons = mesh.polygons();
+    let key = |&(x, y, z): &Point3d| [x, y, z].map(|c| (c * 1e9).round() as i64);
+    let vertices: HashSet<[i64; 3]> = polygons.iter().flat_map(|p| p.vertices().iter().map(key)).collect();
+    (vertices.len(), polygons.len())
+}
+
+/// The normal of a face from its winding, by Newell's method, which also holds for faces of more than three vertices.
+fn normal(face: &[Point3d]) -> Point3d {
+    let n = face.len();
+    (0..n).fold((0.0, 0.0, 0.0), |sum, i| {
+        let (a, b) = (face[i], face[(i + 1) % n]);
+        add(sum, ((a.1 - b.1) * (a.2 + b.2), (a.2 - b.2) * (a.0 + b.0), (a.0 - b.0) * (a.1 + b.1)))
+    })
+}
+
+fn centroid(face: &[Point3d]) -> Point3d {
+    scale(face.iter().fold((0.0, 0.0, 0.0), |sum, &p| add(sum, p)), 1.0 / face.len() as f64)
+}
+
+/// Checks that every face winds counter-clockwise seen from outside, `inside` giving a point inside the shape near a face's centroid.
+fn assert_outward(name: &str, mesh: &Polyhedron, inside: impl Fn(Point3d) -> Point3d) {
+    for polygon in mesh.polygons() {
+        let face = polygon.vertices();
+        assert!(face.len() >= 3, "{name}: a face of {} vertices", face.len());
+        let center = centroid(face);
+        let normal = normal(face);
+        assert!(length(normal) > 1e-12, "{name}: a face without area at {center:?}");
+        assert!(dot(normal, sub(center, inside(center))) > 0.0, "{name}: the face at {center:?} winds inward");
+    }
+}
+
+fn origin(_: Point3d) -> Point3d {
+    (0.0, 0.0, 0.0)
+}
+
+#[test]
+fn platonic_solids() {
+    for (name, mesh, expected) in [
+        ("tetrahedron", primitives::tetrahedron(1.0, Color::White), (4, 4)),
+        ("cube", primitives::cube(1.0, Color::White), (8, 6)),
+        ("octahedron", primitives::octahedron(1.0, Color::White), (6, 8)),
+        ("dodecahedron", primitives::dodecahedron(1.0, Color::White), (20, 12)),
+        ("icosahedron", primitives::icosahedron(1.0, Color::White), (12, 20)),
+    ] {
+        assert_eq!(counts(&mesh), expected, "{name}");
+        assert_outward(name, &mesh, origin);
+        for polygon in mesh.polygons() {
+            for &v in polygon.vertices() {
+                assert!((length(v) - 1.0).abs() < 1e-9, "{name}: vertex {v:?} is not on the unit sphere");
+            }
+        }
+    }
+}
+
+#[test]
+fn spheres() {
+    for (segments, rings) in [(3, 2), (8, 4), (12, 7)] {
+        let mesh = primitives::uv_sphere(2.0, segments, rings, Color::White);
+        let expected = (2 + ((rings - 1) * segments), segments * rings);
+        assert_eq!(counts(&mesh), expected, "uv sphere {segments}x{rings}");
+        assert_outward("uv sphere", &mesh, origin);
+    }
+    // too few segments and rings for a solid are raised to the fewest there can be
+    assert_eq!(counts(&primitives::uv_sphere(1.0, 0, 0, Color::White)), (5, 6));
+
+    for subdivisions in 0..3 {
+        let mesh = primitives::ico_sphere(2.0, subdivisions, Color::White);
+        let faces = 20 * 4usize.pow(subdivisions as u32);
+        assert_eq!(counts(&mesh), ((faces / 2) + 2, faces), "ico sphere {subdivisions}");
+        assert_outward("ico sphere", &mesh, origin);
+    }
+}
+
+#[test]
+fn torus() {
+    let (major, minor) = (3.0, 1.0);
+    let mesh = primitives::torus(major, minor, 12, 6, Color::White);
+    assert_eq!(counts(&mesh), (72, 72));
+    // inside the tube is the circle through its middle
+    assert_outward("torus", &mesh, |(x, _, z)| scale(normalize((x, 0.0, z)), major));
+}
+
+#[test]
+fn cylinder_and_cone() {
+    for segments in [3, 16] {
+        let cylinder = primitives::cylinder(1.0, 2.0, segments, Color::White);
+        assert_eq!(counts(&cylinder), (2 * segments, segments + 2), "cylinder {segments}");
+        assert_outward("cylinder", &cylinder, origin);
+
+        let cone = primitives::cone(1.0, 2.0, segments, Color::White);
+        assert_eq!(counts(&cone), (segments + 1, segments + 1), "cone {segments}");
+        assert_outward("cone", &cone, origin);
+    }
+}
+
+#[test]
+fn plane() {
+    for segments in [1, 4] {
+        let mesh = primitives::plane(2.0, segments, Color::White);
+        assert_eq!(counts(&mesh), ((segments + 1) * (segments + 1), segments * segments), "plane {segments}");
+        // a plane has no outside, but all its cells face the same way
+        let normals: Vec<Point3d> = mesh.polygons().iter().map(|p| normalize(normal(p.vertices()))).collect();
+        for n in &normals {
+            assert!(length(cross(*n, (0.0, 1.0, 0.0))) < 1e-9, "plane {segments}: a cell out of the xz plane");
+            assert!(dot(*n, normals[0]) > 0.0, "plane {segments}: cells facing both ways");
+        }
+    }
+}
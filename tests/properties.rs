@@ -0,0 +1,147 @@
+//! Properties of the quaternion and projection maths, checked on random inputs.
+
+use proptest::prelude::*;
+use dddragon::app::WorldMetrics;
+use dddragon::quaternions::Quaternion;
+use dddragon::scene::{length, project_point, project_to_screen, scale, sub, Point3d, Transform};
+
+/// Relative tolerance for results of a few floating point operations.
+const EPSILON: f64 = 1e-9;
+
+fn close(a: f64, b: f64, magnitude: f64) -> bool {
+    (a - b).abs() <= EPSILON * magnitude.max(1.0)
+}
+
+fn close_points(a: Point3d, b: Point3d, magnitude: f64) -> bool {
+    length(sub(a, b)) <= EPSILON * magnitude.max(1.0)
+}
+
+fn finite(q: &Quaternion) -> bool {
+    let (b, c, d) = q.clone().into();
+    q.len().is_finite() && b.is_finite() && c.is_finite() && d.is_finite()
+}
+
+fn coordinate() -> impl Strategy<Value = f64> {
+    -100.0..100.0
+}
+
+fn point() -> impl Strategy<Value = Point3d> {
+    (coordinate(), coordinate(), coordinate())
+}
+
+/// Quaternions not too close to zero, whose inverse is well defined.
+fn quaternion() -> impl Strategy<Value = Quaternion> {
+    (-10.0..10.0, -10.0..10.0, -10.0..10.0, -10.0..10.0)
+        .prop_map(|(a, b, c, d)| Quaternion::new(a, b, c, d))
+        .prop_filter("too close to zero", |q| q.len() > 1e-3)
+}
+
+fn axis() -> impl Strategy<Value = Quaternion> {
+    point().prop_filter("no direction", |a| length(*a) > 1e-3).prop_map(Quaternion::from)
+}
+
+fn angle() -> impl Strategy<Value = f64> {
+    -10.0..10.0
+}
+
+proptest! {
+    #[test]
+    fn rotating_back_is_the_identity(axis in axis(), theta in angle(), a in point()) {
+        let q = axis.rotatation(theta);
+        let there = Transform::new((0.0, 0.0, 0.0), q.clone());
+        let back = Transform::new((0.0, 0.0, 0.0), q.inverse());
+        prop_assert!(close_points(back.apply(there.apply(a)), a, length(a)));
+    }
+
+    #[test]
+    fn a_quaternion_times_its_inverse_is_one(q in quaternion()) {
+        let one = q.clone() * q.inverse();
+        prop_assert!((one - Quaternion::new(1.0, 0.0, 0.0, 0.0)).len() <= EPSILON);
+    }
+
+    #[test]
+    fn unitize_gives_length_one(q in quaternion()) {
+        prop_assert!(close(q.unitize().len(), 1.0, 1.0));
+    }
+
+    #[test]
+    fn rotations_are_unit_quaternions(axis in axis(), theta in angle()) {
+        prop_assert!(close(axis.rotatation(theta).len(), 1.0, 1.0));
+    }
+
+    #[test]
+    fn product_is_associative(q in quaternion(), p in quaternion(), r in quaternion()) {
+        let left = (q.clone() * p.clone()) * r.clone();
+        let right = q.clone() * (p.clone() * r.clone());
+        let magnitude = q.len() * p.len() * r.len();
+        prop_assert!((left - right).len() <= EPSILON * magnitude.max(1.0));
+    }
+
+    #[test]
+    fn product_multiplies_lengths(q in quaternion(), p in quaternion()) {
+        let product = q.clone() * p.clone();
+        prop_assert!(close(product.len(), q.len() * p.len(), q.len() * p.len()));
+    }
+
+    #[test]
+    fn rotation_preserves_length(axis in axis(), theta in angle(), a in point()) {
+        let rotated = axis.rotate_point(a, theta);
+        prop_assert!(close(length(rotated), length(a), length(a)));
+    }
+
+    #[test]
+    fn rotation_keeps_points_on_the_axis(axis in axis(), theta in angle(), k in -10.0..10.0f64) {
+        let a = scale(axis.clone().into(), k);
+        prop_assert!(close_points(axis.rotate_point(a, theta), a, length(a)));
+    }
+
+    #[test]
+    fn points_on_the_optical_axis_project_to_the_center(
+        pitch in -1.5..1.5f64,
+        yaw in angle(),
+        distance in 1.0..100.0f64,
+        fov in 0.1..3.0f64,
+    ) {
+        let world = WorldMetrics { camera_pitch: pitch, camera_yaw: yaw, fov, ..WorldMetrics::default() };
+        let (x, y) = project_to_screen(scale(world.camera_direction(), distance), &world);
+        prop_assert!(close(x, 0.0, 1.0) && close(y, 0.0, 1.0));
+    }
+
+    #[test]
+    fn points_on_a_ray_from_the_camera_project_to_the_same_place(
+        a in point(),
+        k in 0.1..10.0f64,
+        angles in (angle(), angle(), angle()),
+    ) {
+        let e = (0.0, 0.0, 1.0);
+        let (near, far) = (project_point(a, e, angles), project_point(scale(a, k), e, angles));
+        let magnitude = near.0.abs().max(near.1.abs());
+        prop_assert!(close(near.0, far.0, magnitude) && close(near.1, far.1, magnitude));
+    }
+
+    #[test]
+    fn straight_ahead_projection_divides_by_depth(x in coordinate(), y in coordinate(), z in 1.0..100.0f64) {
+        let (sx, sy) = project_point((x, y, z), (0.0, 0.0, 1.0), (0.0, 0.0, 0.0));
+        prop_assert!(close(sx, x / z, x / z) && close(sy, y / z, y / z));
+    }
+}
+
+#[test]
+fn zero_quaternion_gives_no_nan() {
+    let zero = Quaternion::new(0.0, 0.0, 0.0, 0.0);
+    assert!(finite(&zero.inverse()));
+    assert!(finite(&zero.unitize()));
+    assert!(finite(&zero.rotatation(1.0)));
+    assert!(close(zero.rotatation(1.0).len(), 1.0, 1.0));
+}
+
+#[test]
+fn turning_around_no_axis_leaves_points_alone() {
+    let a = (1.0, -2.0, 3.0);
+    let transform = Transform::from_axis_angle((0.0, 0.0, 0.0), Quaternion::from((0.0, 0.0, 0.0)), 1.0);
+    assert!(close_points(transform.apply(a), a, length(a)));
+
+    let zero = Transform::new((0.0, 0.0, 0.0), Quaternion::new(0.0, 0.0, 0.0, 0.0));
+    let (x, y, z) = zero.apply(a);
+    assert!(x.is_finite() && y.is_finite() && z.is_finite());
+}
@@ -0,0 +1,49 @@
+//! Posting events, recording them and reading the recording back.
+
+use std::env;
+use std::fs;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use dddragon::event::{self, Event, EventHandler, TerminalSetup};
+
+#[test]
+fn recordings_start_with_the_terminal() {
+    let path = env::temp_dir().join(format!("dddragon-{}-input.jsonl", std::process::id()));
+    let setup = TerminalSetup { size: (120, 40), character_ratio: 2.25 };
+    let events = vec![
+        Event::Resize(100, 30),
+        Event::Key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE)),
+        Event::Tick,
+    ];
+
+    let mut handler = EventHandler::replay(events.iter().map(|e| (0.0, e.clone())).collect(), 20);
+    handler.record(&path, setup).unwrap();
+    for event in &events {
+        assert_eq!(&handler.next().unwrap(), event);
+    }
+    drop(handler);
+
+    let recording = event::load(&path);
+    fs::remove_file(&path).unwrap();
+    let (loaded, recorded) = recording.unwrap();
+    assert_eq!(loaded, setup);
+    assert_eq!(recorded.into_iter().map(|(_, e)| e).collect::<Vec<_>>(), events);
+}
+
+#[test]
+fn recordings_without_a_terminal_are_rejected() {
+    let path = env::temp_dir().join(format!("dddragon-{}-old-input.jsonl", std::process::id()));
+    fs::write(&path, "[0.0,\"Tick\"]\n").unwrap();
+    let error = event::load(&path).unwrap_err().to_string();
+    fs::remove_file(&path).unwrap();
+    assert!(error.starts_with(&format!("{}:1: ", path.display())), "{error}");
+}
+
+#[test]
+fn other_producers_post_events() {
+    let handler = EventHandler::replay(Vec::new(), 20);
+    let sender = handler.sender();
+    std::thread::spawn(move || sender.send(Event::Custom("build finished".to_string())).unwrap());
+    // terminal events and ticks may come first
+    let posted = std::iter::from_fn(|| handler.next().ok()).find(|e| matches!(e, Event::Custom(_)));
+    assert_eq!(posted, Some(Event::Custom("build finished".to_string())));
+}
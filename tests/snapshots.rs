@@ -0,0 +1,178 @@
+//! Golden frames of known scenes from fixed camera poses.
+//!
+//! Each snapshot in `tests/snapshots` holds the symbols of a frame followed by
+//! the colour of every cell, so changes to the projection, the line interpolation
+//! or the drawing order show up as a diff. After an intended change, rewrite the
+//! snapshots with `BLESS=1 cargo test --test snapshots` and review them.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tui::buffer::Buffer;
+use tui::style::Color;
+use dddragon::app::App;
+use dddragon::clock::FixedStep;
+use dddragon::headless;
+use dddragon::level::Level;
+use dddragon::primitives;
+use dddragon::render::Renderer;
+use dddragon::scene::{Animation, Polyhedron, Transform};
+use dddragon::quaternions::Quaternion;
+
+const WIDTH: u16 = 60;
+const HEIGHT: u16 = 20;
+
+/// A camera pose: position, pitch and yaw.
+type Pose = ((f64, f64, f64), f64, f64);
+
+const FRONT: Pose = ((0.0, 0.0, 0.0), 0.0, 0.0);
+
+fn app(scene: Polyhedron, (position, pitch, yaw): Pose) -> App {
+    let mut app = App::new();
+    app.clock = Box::new(FixedStep::new(0.0, 0.0));
+    app.scene = scene;
+    app.world.set_camera_position(position);
+    app.world.camera_pitch = pitch;
+    app.world.camera_yaw = yaw;
+    app
+}
+
+fn demo_object(name: &str) -> Polyhedron {
+    let level = Level::demo();
+    let object = level.objects.iter().find(|o| o.name == name).expect("the demo level has this object");
+    object.build(Path::new("")).unwrap()
+}
+
+fn cube() -> Polyhedron {
+    primitives::cube(2.0, Color::Cyan).with_transform(Transform::new((0.0, 0.0, 5.0), Quaternion::new(1.0, 0.0, 0.0, 0.0)))
+}
+
+fn demo() -> Polyhedron {
+    Level::demo().build(Path::new("")).unwrap()
+}
+
+/// One letter per cell for its colour, `.` for cells without one.
+fn color_letter(color: Color) -> char {
+    match color {
+        Color::Reset => '.',
+        Color::Black => 'k',
+        Color::Red => 'r',
+        Color::Green => 'g',
+        Color::Yellow => 'y',
+        Color::Blue => 'b',
+        Color::Magenta => 'm',
+        Color::Cyan => 'c',
+        Color::Gray => 'a',
+        Color::DarkGray => 'd',
+        Color::White => 'w',
+        Color::LightRed => 'R',
+        Color::LightGreen => 'G',
+        Color::LightYellow => 'Y',
+        Color::LightBlue => 'B',
+        Color::LightMagenta => 'M',
+        Color::LightCyan => 'C',
+        Color::Rgb(..) | Color::Indexed(_) => '#',
+    }
+}
+
+fn snapshot(buffer: &Buffer) -> String {
+    let mut text = headless::lines(buffer).join("\n");
+    text.push_str("\n\n");
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let colors: String = (area.left()..area.right()).map(|x| color_letter(buffer.get(x, y).fg)).collect();
+        text.push_str(&colors);
+        text.push('\n');
+    }
+    text
+}
+
+fn check(name: &str, mut app: App) {
+    let actual = snapshot(&headless::render(&mut app, WIDTH, HEIGHT).unwrap());
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", &format!("{name}.txt")].iter().collect();
+
+    if env::var_os("BLESS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("no snapshot {}, create it with BLESS=1", path.display()));
+    if expected != actual {
+        let mut diff = String::new();
+        let (expected, actual): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+        for i in 0..expected.len().max(actual.len()) {
+            let (e, a) = (expected.get(i).unwrap_or(&""), actual.get(i).unwrap_or(&""));
+            if e != a {
+                diff.push_str(&format!("{i:3} - {e}\n{i:3} + {a}\n"));
+            }
+        }
+        panic!("frame differs from {}, rewrite it with BLESS=1 if this is intended:\n{diff}", path.display());
+    }
+}
+
+#[test]
+fn cube_front() {
+    check("cube_front", app(cube(), FRONT));
+}
+
+#[test]
+fn cube_from_above_left() {
+    check("cube_from_above_left", app(cube(), ((-2.0, 1.5, 1.0), 0.3, 0.4)));
+}
+
+#[test]
+fn cube_solid() {
+    let mut app = app(cube(), ((1.5, -1.0, 1.0), -0.2, -0.3));
+    app.wireframe = false;
+    check("cube_solid", app);
+}
+
+#[test]
+fn cube_ascii() {
+    let mut app = app(cube(), FRONT);
+    app.renderer = Renderer::Ascii;
+    check("cube_ascii", app);
+}
+
+#[test]
+fn cube_halfblock() {
+    let mut app = app(cube(), FRONT);
+    app.renderer = Renderer::HalfBlock;
+    check("cube_halfblock", app);
+}
+
+#[test]
+fn pentagram_front() {
+    check("pentagram_front", app(demo_object("pentagram"), ((0.0, 0.0, 4.0), 0.0, 0.0)));
+}
+
+#[test]
+fn pentagram_turned() {
+    check("pentagram_turned", app(demo_object("pentagram"), ((1.0, 0.5, 4.5), -0.2, -0.5)));
+}
+
+#[test]
+fn eye_front() {
+    check("eye_front", app(demo_object("eye"), ((-1.0, -1.0, 5.5), 0.0, 0.0)));
+}
+
+#[test]
+fn eye_tilted() {
+    check("eye_tilted", app(demo_object("eye"), ((-0.5, 0.5, 5.0), 0.25, -0.2)));
+}
+
+/// The eye, pentagram and dodecahedron overlap, so the drawing order decides the colours.
+#[test]
+fn demo_overlapping() {
+    check("demo_overlapping", app(demo(), ((0.5, -0.3, 3.0), 0.05, 0.15)));
+}
+
+/// A spinning cube drawn by a clock that stands still at one second in.
+#[test]
+fn cube_spinning() {
+    let spinning = cube().with_animation(Animation::Spin { axis: (0.0, 1.0, 0.0), speed: 0.5 });
+    let mut app = app(spinning, FRONT);
+    app.clock = Box::new(FixedStep::new(1000.0, 0.0));
+    check("cube_spinning", app);
+}
@@ -0,0 +1,104 @@
+//! Heightmaps read from CSV and PGM files.
+
+use std::env;
+use std::fs;
+use dddragon::surface::Surface;
+
+/// Loads `contents` as a heightmap file named `name`, one unit between samples.
+fn load(name: &str, contents: &[u8]) -> Result<Surface, String> {
+    let path = env::temp_dir().join(format!("dddragon-{}-{name}", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    let surface = Surface::load(&path, 1.0, 1.0).map_err(|e| e.to_string());
+    fs::remove_file(&path).unwrap();
+    surface
+}
+
+/// The heights of a surface, row by row.
+fn heights(surface: &Surface) -> Vec<Vec<Option<f64>>> {
+    surface.map.iter().map(|row| row.iter().map(|p| p.map(|(_, y, _)| y)).collect()).collect()
+}
+
+#[test]
+fn csv() {
+    let surface = load("heights.csv", b"0, 1, 2\n3,4,5\n\n").unwrap();
+    assert_eq!(heights(&surface), [[Some(0.0), Some(1.0), Some(2.0)], [Some(3.0), Some(4.0), Some(5.0)]]);
+    // laid out one unit apart, centered on the origin
+    assert_eq!(surface.map[0][0], Some((-1.0, 0.0, -0.5)));
+    assert_eq!(surface.map[1][2], Some((1.0, 5.0, 0.5)));
+    assert_eq!(surface.height_range(), Some((0.0, 5.0)));
+}
+
+#[test]
+fn csv_holes() {
+    let surface = load("holes.csv", b"1,,nan\n-,inf,2\n3\n").unwrap();
+    assert_eq!(heights(&surface), [vec![Some(1.0), None, None], vec![None, None, Some(2.0)], vec![Some(3.0)]]);
+}
+
+#[test]
+fn empty_csv() {
+    assert_eq!(load("empty.csv", b"\n  \n").unwrap_err(), "heightmap has no rows");
+}
+
+#[test]
+fn plain_pgm() {
+    let surface = load("plain.pgm", b"P2\n# a comment\n3 2\n4\n0 1 2\n3 4 4\n").unwrap();
+    assert_eq!(heights(&surface), [[Some(0.0), Some(0.25), Some(0.5)], [Some(0.75), Some(1.0), Some(1.0)]]);
+}
+
+#[test]
+fn raw_pgm() {
+    let mut bytes = b"P5 2 2 255\n".to_vec();
+    bytes.extend([0, 51, 102, 255]);
+    let surface = load("raw.pgm", &bytes).unwrap();
+    assert_eq!(heights(&surface), [[Some(0.0), Some(0.2)], [Some(0.4), Some(1.0)]]);
+
+    // samples above 255 take two bytes, most significant first
+    let mut bytes = b"P5 2 1 1000\n".to_vec();
+    bytes.extend([0x01, 0xf4, 0x03, 0xe8]);
+    let surface = load("raw16.pgm", &bytes).unwrap();
+    assert_eq!(heights(&surface), [[Some(0.5), Some(1.0)]]);
+}
+
+#[test]
+fn extra_pgm_samples_are_ignored() {
+    let surface = load("extra.pgm", b"P2 2 1 10 5 10 7 8\n").unwrap();
+    assert_eq!(heights(&surface), [[Some(0.5), Some(1.0)]]);
+}
+
+#[test]
+fn malformed_pgm() {
+    for (name, contents, error) in [
+        ("magic.pgm", &b"P3 1 1 255 0 0 0"[..], "unsupported PGM format 'P3'"),
+        ("header.pgm", b"P2 2 2", "PGM header is truncated"),
+        ("comment.pgm", b"P2 2 2 # 255 1 2 3 4", "PGM header is truncated"),
+        ("width.pgm", b"P2 two 2 255\n1 2 3 4", "invalid PGM header field 'two'"),
+        ("negative.pgm", b"P2 -2 2 255\n1 2 3 4", "invalid PGM header field '-2'"),
+        ("sample.pgm", b"P2 2 1 255\n1 x", "invalid PGM header field 'x'"),
+        ("zero.pgm", b"P2 1 1 0\n0", "invalid PGM maximum value 0"),
+        ("deep.pgm", b"P2 1 1 65536\n0", "invalid PGM maximum value 65536"),
+    ] {
+        assert_eq!(load(name, contents).unwrap_err(), error, "{name}");
+    }
+}
+
+#[test]
+fn pgm_size_must_match_samples() {
+    assert_eq!(load("short.pgm", b"P2 3 2 255\n1 2 3 4 5").unwrap_err(), "PGM has 5 samples, expected 6");
+    assert_eq!(load("short-raw.pgm", b"P5 3 2 255\n\x01\x02\x03").unwrap_err(), "PGM has 3 samples, expected 6");
+    // two bytes a sample, so an odd byte left over is no sample
+    assert_eq!(load("odd-raw.pgm", b"P5 2 1 1000\n\x01\x02\x03").unwrap_err(), "PGM has 1 samples, expected 2");
+}
+
+#[test]
+fn oversized_pgm() {
+    let huge = format!("P5 {} 2 255\n\x00\x00", usize::MAX);
+    assert_eq!(load("overflow.pgm", huge.as_bytes()).unwrap_err(), format!("PGM size {}x2 is too large", usize::MAX));
+    // large enough to need a lot of memory, but with a header that doesn't overflow, fails on the missing samples
+    assert_eq!(load("large.pgm", b"P5 100000 100000 255\n\x00").unwrap_err(), "PGM has 1 samples, expected 10000000000");
+}
+
+#[test]
+fn missing_file() {
+    let path = env::temp_dir().join(format!("dddragon-{}-missing.csv", std::process::id()));
+    assert!(Surface::load(&path, 1.0, 1.0).is_err());
+}